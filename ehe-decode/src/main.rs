@@ -0,0 +1,133 @@
+//! Decode device error codes and serialized errors
+//!
+//! Reads lines from the files given as arguments (or stdin if none or `-`) and decodes:
+//!
+//! * numeric codes, e.g. `0x0203` to `i2c: NoAcknowledge(Address)`
+//! * hex dumped postcard frames of a serialized `Error`,
+//!   e.g. `03 69 32 63 02 00 02 00 55` to `i2c: NoAcknowledge(Address) <- 55`
+//! * other lines are echoed and annotated with the codes they contain
+
+use std::{
+    env, fs,
+    io::{self, BufRead},
+    process::ExitCode,
+};
+
+use embedded_hal_error::{CodeEntry, DetailCode, HalErrorKind};
+use serde::de::DeserializeSeed;
+
+fn describe(code: u16) -> Option<String> {
+    CodeEntry::lookup(code).map(|e| format!("{}: {}", e.family, e.variant))
+}
+
+fn parse_code(token: &str) -> Option<u16> {
+    u16::from_str_radix(token.strip_prefix("0x")?, 16).ok()
+}
+
+fn parse_hex(line: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = line.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|c| u8::from_str_radix(std::str::from_utf8(c).ok()?, 16).ok())
+        .collect()
+}
+
+fn frame(bytes: &[u8]) -> postcard::Result<String> {
+    let (family, rest) = postcard::take_from_bytes::<&str>(bytes)?;
+    let (_kind, rest) = postcard::take_from_bytes::<HalErrorKind>(rest)?;
+    let mut de = postcard::Deserializer::from_bytes(rest);
+    let code = DetailCode(family).deserialize(&mut de)?;
+    let inner = de.finalize()?;
+    let inner: Vec<_> = inner.iter().map(|b| format!("{b:02x}")).collect();
+    Ok(format!(
+        "{} <- {}",
+        describe(code).ok_or(postcard::Error::DeserializeBadEncoding)?,
+        inner.join(" ")
+    ))
+}
+
+fn decode(line: &str) -> String {
+    let trimmed = line.trim();
+    if let Some(desc) = parse_code(trimmed).and_then(describe) {
+        return desc;
+    }
+    if let Some(desc) = parse_hex(trimmed).and_then(|bytes| frame(&bytes).ok()) {
+        return desc;
+    }
+    let mut out = line.to_string();
+    for token in line.split(|c: char| !c.is_ascii_alphanumeric()) {
+        if let Some(desc) = parse_code(token).and_then(describe) {
+            out += &format!(" [{token}: {desc}]");
+        }
+    }
+    out
+}
+
+fn run(input: impl BufRead) -> io::Result<()> {
+    for line in input.lines() {
+        println!("{}", decode(&line?));
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let mut paths: Vec<String> = env::args().skip(1).collect();
+    if paths.is_empty() {
+        paths.push("-".into());
+    }
+    let mut ret = ExitCode::SUCCESS;
+    for path in paths {
+        let res = if path == "-" {
+            run(io::stdin().lock())
+        } else {
+            fs::File::open(&path).and_then(|f| run(io::BufReader::new(f)))
+        };
+        if let Err(err) = res {
+            eprintln!("{path}: {err}");
+            ret = ExitCode::FAILURE;
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+
+    #[test]
+    fn code() {
+        assert_eq!(decode("0x0203"), "i2c: NoAcknowledge(Address)");
+        assert_eq!(decode(" 0x070d "), "io: TimedOut");
+    }
+
+    #[test]
+    fn frame() {
+        use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+
+        let err: embedded_hal_error::I2cError<_> =
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address).into();
+        let mut buf = [0; 32];
+        let bytes = postcard::to_slice(&err.map(|_| 0x55u8), &mut buf).unwrap();
+        let hex: Vec<_> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(decode(&hex.join(" ")), "i2c: NoAcknowledge(Address) <- 55");
+        assert_eq!(decode(&hex.concat()), "i2c: NoAcknowledge(Address) <- 55");
+
+        let err = embedded_hal_error::UnknownError::unknown(0x55u8);
+        let bytes = postcard::to_slice(&err, &mut buf).unwrap();
+        let hex: Vec<_> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(decode(&hex.concat()), "unknown: Unknown <- 55");
+    }
+
+    #[test]
+    fn log() {
+        assert_eq!(
+            decode("[WARN] sensor: read failed (0x0602), retrying"),
+            "[WARN] sensor: read failed (0x0602), retrying [0x0602: serial: FrameFormat]"
+        );
+        assert_eq!(decode("nothing to see"), "nothing to see");
+        assert_eq!(decode("0xffff"), "0xffff");
+    }
+}
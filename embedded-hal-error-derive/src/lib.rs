@@ -0,0 +1,292 @@
+#![deny(rust_2018_compatibility)]
+#![deny(rust_2018_idioms)]
+#![warn(missing_docs)]
+#![forbid(unsafe_code)]
+
+//! Derive macro for driver error enums wrapping `embedded-hal-error` errors.
+//!
+//! Use it through the `derive` feature of `embedded-hal-error`.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, GenericArgument, LitStr,
+    PathArguments, Type,
+};
+
+/// Derive `From`, `Display` and `core::error::Error` for a driver error enum
+///
+/// Variants annotated with `#[hal(<family>)]` hold a single
+/// `embedded_hal_error::Error<E, ErrorKind>` of that family, e.g. `SpiError<E>` for `#[hal(spi)]`.
+/// The families are `digital`, `i2c`, `pwm`, `spi`, `can`, `serial`, `io` and `nor_flash`.
+/// For each of them this generates:
+///
+/// * `From<Error<E, ErrorKind>>`
+/// * `From<E>` for `E` implementing the family `Error` trait if this is the only HAL variant.
+///   With several HAL variants the `From<E>` impls of generic drivers would overlap.
+///   Select one using `#[hal(<family>, from)]` and convert the others
+///   with e.g. `.map_err(DigitalError::from)?`.
+/// * `Display` as the family name
+/// * `core::error::Error::source()` as the wrapper
+///
+/// Other variants display as their `#[hal(display = "...")]` format string, or their name.
+/// Fields are available to the format string as `_0`, `_1`, ... or by their name.
+/// They have no source.
+///
+/// ```ignore
+/// #[derive(Debug, HalError)]
+/// pub enum Error<S: spi::Error, P: digital::Error> {
+///     #[hal(spi, from)]
+///     Spi(SpiError<S>),
+///     #[hal(digital)]
+///     Irq(DigitalError<P>),
+///     #[hal(display = "invalid chip ID {_0:#x}")]
+///     ChipId(u8),
+/// }
+/// ```
+#[proc_macro_derive(HalError, attributes(hal))]
+pub fn derive_hal_error(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Supported family
+struct Family {
+    /// Upstream module re-exported in `embedded_hal_error::derive`, e.g. `nor_flash`
+    module: &'static str,
+    /// `Family::NAME`, e.g. `"nor-flash"`
+    name: &'static str,
+    /// Family `Error` trait
+    error: &'static str,
+    /// Family `ErrorKind`
+    kind: &'static str,
+}
+
+impl Family {
+    const ALL: &'static [Self] = &[
+        Self::new("digital", "digital", "Error", "ErrorKind"),
+        Self::new("i2c", "i2c", "Error", "ErrorKind"),
+        Self::new("pwm", "pwm", "Error", "ErrorKind"),
+        Self::new("spi", "spi", "Error", "ErrorKind"),
+        Self::new("can", "can", "Error", "ErrorKind"),
+        Self::new("serial", "serial", "Error", "ErrorKind"),
+        Self::new("io", "io", "Error", "ErrorKind"),
+        Self::new(
+            "nor_flash",
+            "nor-flash",
+            "NorFlashError",
+            "NorFlashErrorKind",
+        ),
+    ];
+
+    const fn new(
+        module: &'static str,
+        name: &'static str,
+        error: &'static str,
+        kind: &'static str,
+    ) -> Self {
+        Self {
+            module,
+            name,
+            error,
+            kind,
+        }
+    }
+
+    fn get(ident: &syn::Ident) -> syn::Result<&'static Self> {
+        Self::ALL
+            .iter()
+            .find(|family| ident == family.module)
+            .ok_or_else(|| {
+                let modules: Vec<_> = Self::ALL.iter().map(|family| family.module).collect();
+                syn::Error::new(
+                    ident.span(),
+                    format!("unknown family, expected one of {}", modules.join(", ")),
+                )
+            })
+    }
+
+    /// Path of the item `name` of the family module
+    fn path(&self, name: &str) -> TokenStream {
+        let module = format_ident!("{}", self.module);
+        let name = format_ident!("{}", name);
+        quote!(::embedded_hal_error::derive::#module::#name)
+    }
+}
+
+/// Parsed `#[hal(...)]` variant attribute
+#[derive(Default)]
+struct Attr {
+    family: Option<&'static Family>,
+    from: bool,
+    display: Option<LitStr>,
+}
+
+impl Attr {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut attr = Self::default();
+        for a in attrs.iter().filter(|a| a.path().is_ident("hal")) {
+            a.parse_nested_meta(|meta| {
+                if meta.path.is_ident("from") {
+                    if attr.from {
+                        return Err(meta.error("duplicate `from`"));
+                    }
+                    attr.from = true;
+                } else if meta.path.is_ident("display") {
+                    attr.display = Some(meta.value()?.parse()?);
+                } else if let Some(ident) = meta.path.get_ident() {
+                    if attr.family.is_some() {
+                        return Err(meta.error("duplicate family"));
+                    }
+                    attr.family = Some(Family::get(ident)?);
+                } else {
+                    return Err(meta.error("expected a family, `from`, or `display`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(attr)
+    }
+}
+
+/// The HAL error `E` of `Error<E, ErrorKind>` or an alias `SpiError<E>`
+fn inner_type(ty: &Type) -> syn::Result<&Type> {
+    if let Type::Path(path) = ty {
+        if let Some(PathArguments::AngleBracketed(args)) =
+            path.path.segments.last().map(|s| &s.arguments)
+        {
+            if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                return Ok(inner);
+            }
+        }
+    }
+    Err(syn::Error::new(
+        ty.span(),
+        "expected `Error<E, ErrorKind>` or an alias like `SpiError<E>`",
+    ))
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "HalError can only be derived for enums",
+        ));
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut hal = vec![];
+    let mut display = vec![];
+    let mut source = vec![];
+    for variant in data.variants.iter() {
+        let ident = &variant.ident;
+        let attr = Attr::parse(&variant.attrs)?;
+        if let Some(family) = attr.family {
+            let field = match &variant.fields {
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+                _ => {
+                    return Err(syn::Error::new(
+                        variant.span(),
+                        "HAL variants must have a single unnamed field",
+                    ))
+                }
+            };
+            if let Some(display) = attr.display {
+                return Err(syn::Error::new(
+                    display.span(),
+                    "HAL variants display as their family",
+                ));
+            }
+            let name = family.name;
+            display.push(quote!(Self::#ident(_) => f.write_str(#name)));
+            source.push(quote!(Self::#ident(err) => ::core::option::Option::Some(err)));
+            if attr.from && hal.iter().any(|(_, _, _, from)| *from) {
+                return Err(syn::Error::new(variant.span(), "duplicate `from`"));
+            }
+            hal.push((ident, family, &field.ty, attr.from));
+        } else {
+            if attr.from {
+                return Err(syn::Error::new(variant.span(), "`from` requires a family"));
+            }
+            let (pattern, fmt) = match &variant.fields {
+                Fields::Unit => (quote!(), None),
+                Fields::Unnamed(fields) => {
+                    let vars = (0..fields.unnamed.len()).map(|i| format_ident!("_{}", i));
+                    (quote!((#(#vars),*)), attr.display)
+                }
+                Fields::Named(fields) => {
+                    let vars = fields.named.iter().map(|f| &f.ident);
+                    (quote!({ #(#vars),* }), attr.display)
+                }
+            };
+            let fmt = fmt.unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+            display.push(quote!(Self::#ident #pattern => ::core::write!(f, #fmt)));
+            source.push(quote!(Self::#ident { .. } => ::core::option::Option::None));
+        }
+    }
+
+    let single = hal.len() == 1;
+    let mut from = vec![];
+    for (ident, family, ty, force) in hal.iter() {
+        let kind = family.path(family.kind);
+        let value = quote_spanned!(ty.span()=> ::embedded_hal_error::Error<_, #kind>);
+        from.push(quote! {
+            impl #impl_generics ::core::convert::From<#ty> for #name #ty_generics #where_clause {
+                fn from(value: #ty) -> Self {
+                    let value: #value = value;
+                    Self::#ident(value)
+                }
+            }
+        });
+        if single || *force {
+            let inner = inner_type(ty)?;
+            let error = family.path(family.error);
+            let mut generics = input.generics.clone();
+            generics
+                .make_where_clause()
+                .predicates
+                .push(syn::parse_quote!(#inner: #error));
+            let where_clause = &generics.where_clause;
+            from.push(quote! {
+                impl #impl_generics ::core::convert::From<#inner> for #name #ty_generics #where_clause {
+                    #[track_caller]
+                    fn from(value: #inner) -> Self {
+                        Self::#ident(::embedded_hal_error::Error::<#inner, #kind>::from(value))
+                    }
+                }
+            });
+        }
+    }
+
+    let mut error_generics = input.generics.clone();
+    let predicates = &mut error_generics.make_where_clause().predicates;
+    predicates.push(syn::parse_quote!(Self: ::core::fmt::Debug));
+    for (_, _, ty, _) in hal.iter() {
+        predicates.push(syn::parse_quote!(#ty: ::core::error::Error + 'static));
+    }
+    let error_where_clause = &error_generics.where_clause;
+
+    Ok(quote! {
+        #(#from)*
+
+        impl #impl_generics ::core::fmt::Display for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #(#display,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::error::Error for #name #ty_generics #error_where_clause {
+            fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
+                match self {
+                    #(#source,)*
+                }
+            }
+        }
+    })
+}
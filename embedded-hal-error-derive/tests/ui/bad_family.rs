@@ -0,0 +1,9 @@
+use embedded_hal_error::{HalError, SpiError};
+
+#[derive(Debug, HalError)]
+enum Error<S> {
+    #[hal(spy)]
+    Spi(SpiError<S>),
+}
+
+fn main() {}
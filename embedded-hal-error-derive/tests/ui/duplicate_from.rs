@@ -0,0 +1,11 @@
+use embedded_hal_error::{DigitalError, HalError, SpiError};
+
+#[derive(Debug, HalError)]
+enum Error<S, P> {
+    #[hal(spi, from)]
+    Spi(SpiError<S>),
+    #[hal(digital, from)]
+    Irq(DigitalError<P>),
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use embedded_hal_error::{HalError, SpiError};
+
+#[derive(Debug, HalError)]
+struct Error<S>(SpiError<S>);
+
+fn main() {}
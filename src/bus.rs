@@ -0,0 +1,134 @@
+//! `embedded-hal-bus` support
+
+use core::{error, fmt};
+use embedded_hal::{digital, spi};
+use embedded_hal_bus::spi::DeviceError as BusDeviceError;
+
+use crate::{DigitalError, SpiError};
+
+/// Wrap an `embedded_hal_bus::spi::DeviceError` to provide [`core::error::Error`]
+///
+/// Depending on the variant, [`core::error::Error::source()`] is either the
+/// wrapped SPI bus error or the wrapped chip select pin error,
+/// each in turn having its `ErrorKind` as source.
+#[derive(Debug)]
+pub enum DeviceError<BUS, CS> {
+    /// SPI bus error
+    Spi(SpiError<BUS>),
+    /// Chip select pin error
+    Cs(DigitalError<CS>),
+}
+
+impl<BUS, CS> DeviceError<BUS, CS> {
+    /// Extract the inner `DeviceError`
+    pub fn into_inner(self) -> BusDeviceError<BUS, CS> {
+        match self {
+            Self::Spi(e) => BusDeviceError::Spi(e.into_inner()),
+            Self::Cs(e) => BusDeviceError::Cs(e.into_inner()),
+        }
+    }
+}
+
+impl<BUS: spi::Error, CS: digital::Error> From<BusDeviceError<BUS, CS>> for DeviceError<BUS, CS> {
+    fn from(value: BusDeviceError<BUS, CS>) -> Self {
+        match value {
+            BusDeviceError::Spi(e) => Self::Spi(e.into()),
+            BusDeviceError::Cs(e) => Self::Cs(e.into()),
+        }
+    }
+}
+
+impl<BUS: fmt::Debug, CS: fmt::Debug> fmt::Display for DeviceError<BUS, CS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spi(e) => write!(f, "SPI bus error: {e}"),
+            Self::Cs(e) => write!(f, "SPI CS error: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<BUS: defmt::Format, CS: defmt::Format> defmt::Format for DeviceError<BUS, CS> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            Self::Spi(e) => defmt::write!(f, "SPI bus error: {}", e),
+            Self::Cs(e) => defmt::write!(f, "SPI CS error: {}", e),
+        }
+    }
+}
+
+impl<BUS: fmt::Debug + 'static, CS: fmt::Debug + 'static> error::Error for DeviceError<BUS, CS> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(match self {
+            Self::Spi(e) => e,
+            Self::Cs(e) => e,
+        })
+    }
+}
+
+impl<BUS: spi::Error, CS: fmt::Debug> spi::Error for DeviceError<BUS, CS> {
+    fn kind(&self) -> spi::ErrorKind {
+        match self {
+            Self::Spi(e) => e.kind(),
+            Self::Cs(_) => spi::ErrorKind::ChipSelectFault,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::error::Error as _;
+    use embedded_hal::{digital, spi};
+    use embedded_hal_bus::spi::DeviceError as BusDeviceError;
+
+    use super::DeviceError;
+
+    #[derive(Debug)]
+    struct BusError;
+    impl spi::Error for BusError {
+        fn kind(&self) -> spi::ErrorKind {
+            spi::ErrorKind::Overrun
+        }
+    }
+
+    #[derive(Debug)]
+    struct PinError;
+    impl digital::Error for PinError {
+        fn kind(&self) -> digital::ErrorKind {
+            digital::ErrorKind::Other
+        }
+    }
+
+    fn action(fail_cs: bool) -> Result<(), DeviceError<BusError, PinError>> {
+        Err(if fail_cs {
+            BusDeviceError::Cs(PinError)
+        } else {
+            BusDeviceError::Spi(BusError)
+        })?
+    }
+
+    #[test]
+    fn inspect_spi() {
+        use spi::Error as _;
+
+        let err = action(false).unwrap_err();
+        assert!(matches!(err.kind(), spi::ErrorKind::Overrun));
+        let err_dyn = err.source().unwrap();
+        let _: &crate::SpiError<BusError> = err_dyn.downcast_ref().unwrap();
+        let kind: &spi::ErrorKind = err_dyn.source().unwrap().downcast_ref().unwrap();
+        assert!(matches!(kind, spi::ErrorKind::Overrun));
+    }
+
+    #[test]
+    fn inspect_cs() {
+        use spi::Error as _;
+
+        let err = action(true).unwrap_err();
+        assert!(matches!(err.kind(), spi::ErrorKind::ChipSelectFault));
+        let err_dyn = err.source().unwrap();
+        let _: &crate::DigitalError<PinError> = err_dyn.downcast_ref().unwrap();
+        let kind: &digital::ErrorKind = err_dyn.source().unwrap().downcast_ref().unwrap();
+        assert!(matches!(kind, digital::ErrorKind::Other));
+        assert!(matches!(err.into_inner(), BusDeviceError::Cs(PinError)));
+    }
+}
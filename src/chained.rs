@@ -0,0 +1,131 @@
+//! Source chains through the inner error
+
+use core::{error, fmt};
+
+use crate::Error;
+
+/// [`Error`] with the inner `Error` as [`core::error::Error::source()`]
+///
+/// [`Error`] only requires `E: Debug` and hides any source chain of `E`.
+/// If `E` implements [`core::error::Error`] this exposes it instead:
+/// `Chained` displays the `ErrorKind` and its source is the inner `E`,
+/// followed by the source chain of `E`.
+///
+/// [`HalErrorKind::find()`](crate::HalErrorKind::find) can not downcast the generic
+/// `Chained` and does not see the `ErrorKind`. Use [`Error::hal_kind()`] through `Deref`
+/// or `HalErrorKind::find(&*chained)` on the inner [`Error`].
+pub struct Chained<E, K>(Error<E, K>);
+
+impl<E, K> Chained<E, K> {
+    /// Extract the inner [`Error`]
+    pub fn into_inner(self) -> Error<E, K> {
+        self.0
+    }
+}
+
+impl<E, K> From<Error<E, K>> for Chained<E, K> {
+    fn from(value: Error<E, K>) -> Self {
+        Self(value)
+    }
+}
+
+impl<E, K> From<E> for Chained<E, K>
+where
+    Error<E, K>: From<E>,
+{
+    #[track_caller]
+    fn from(value: E) -> Self {
+        Self(Error::from(value))
+    }
+}
+
+impl<E, K> core::ops::Deref for Chained<E, K> {
+    type Target = Error<E, K>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, K: fmt::Display> fmt::Display for Chained<E, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.kind.fmt(f)
+    }
+}
+
+impl<E: fmt::Debug, K: fmt::Debug> fmt::Debug for Chained<E, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Chained")
+            .field("kind", &self.0.kind)
+            .field("inner", &self.0.inner)
+            .finish()
+    }
+}
+
+impl<E: error::Error + 'static, K: fmt::Debug + fmt::Display> error::Error for Chained<E, K> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.0.inner)
+    }
+}
+
+#[cfg(all(test, feature = "i2c"))]
+mod tests {
+    use core::{error::Error as _, fmt};
+    use embedded_hal::i2c::{self, ErrorKind};
+
+    use super::Chained;
+
+    /// OS level error
+    #[derive(Debug)]
+    struct Os(i32);
+    impl fmt::Display for Os {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "os error {}", self.0)
+        }
+    }
+    impl core::error::Error for Os {}
+
+    /// HAL error wrapping the OS error
+    #[derive(Debug)]
+    struct Error(Os);
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("ioctl failed")
+        }
+    }
+    impl core::error::Error for Error {
+        fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+    impl i2c::Error for Error {
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Bus
+        }
+    }
+
+    #[test]
+    fn inspect() {
+        let err: Chained<_, ErrorKind> = Error(Os(5)).into();
+        assert_eq!(err.hal_kind(), crate::HalErrorKind::Bus); // Deref
+        let err_dyn = err.source().unwrap();
+        let _: &Error = err_dyn.downcast_ref().unwrap();
+        let os: &Os = err_dyn.source().unwrap().downcast_ref().unwrap();
+        assert_eq!(os.0, 5);
+        assert_eq!(
+            crate::HalErrorKind::find(&*err),
+            Some(crate::HalErrorKind::Bus)
+        );
+    }
+
+    #[test]
+    fn report() {
+        extern crate std;
+        use std::format;
+
+        let err: Chained<Error, ErrorKind> = crate::I2cError::from(Error(Os(5))).into();
+        assert_eq!(
+            format!("{}", crate::Report::new(&err)),
+            format!("{}: ioctl failed: os error 5", ErrorKind::Bus)
+        );
+    }
+}
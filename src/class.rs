@@ -0,0 +1,165 @@
+//! Transient/fatal classification of HAL errors
+
+use crate::{Error, HalErrorKind};
+
+/// Error class
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Class {
+    /// Retrying the operation may succeed
+    Transient,
+    /// The operation can not succeed with the current configuration
+    /// (addressing, alignment, parameters, unsupported features)
+    Configuration,
+    /// Retrying will not help
+    Fatal,
+}
+
+/// Classification policy
+///
+/// Implement this to override the [`DefaultPolicy`].
+/// Defer to `DefaultPolicy::classify()` for the kinds that need no special treatment.
+pub trait Policy {
+    /// Classify a unified kind
+    fn classify(kind: HalErrorKind) -> Class;
+}
+
+/// Default classification policy
+///
+/// | Class | [`HalErrorKind`] | Upstream `ErrorKind`s |
+/// |---|---|---|
+/// | Transient | `Bus` | i2c `Bus`, can `Bit` |
+/// | Transient | `ArbitrationLoss` | i2c `ArbitrationLoss` |
+/// | Transient | `NoAcknowledge(_)` | i2c `NoAcknowledge(_)`, can `Acknowledge` |
+/// | Transient | `Overrun` | i2c, spi, can, serial `Overrun` |
+/// | Transient | `Framing` | spi, serial `FrameFormat`, can `Stuff`, `Form` |
+/// | Transient | `Parity`, `Noise` | serial `Parity`, `Noise` |
+/// | Transient | `Crc` | can `Crc` |
+/// | Transient | `Timeout`, `Interrupted` | io `TimedOut`, `Interrupted` |
+/// | Configuration | `Unsupported`, `InvalidInput`, `NotFound`, `PermissionDenied`, `AddrInUse`, `AddrNotAvailable`, `AlreadyExists` | io variants of the same name |
+/// | Configuration | `NotAligned`, `OutOfBounds` | nor-flash variants of the same name |
+/// | Fatal | `ModeFault`, `ChipSelectFault` | spi variants of the same name |
+/// | Fatal | `NotConnected`, `ConnectionRefused`, `ConnectionReset`, `ConnectionAborted`, `BrokenPipe`, `OutOfMemory`, `WriteZero`, `InvalidData` | io variants of the same name |
+/// | Fatal | `Other` | all `Other` |
+///
+/// A missing acknowledge is transient as devices commonly do not acknowledge
+/// their address while busy (e.g. EEPROM write cycles).
+#[derive(Debug, Copy, Clone, Default)]
+pub struct DefaultPolicy;
+
+impl Policy for DefaultPolicy {
+    fn classify(kind: HalErrorKind) -> Class {
+        use HalErrorKind::*;
+        match kind {
+            Bus | ArbitrationLoss | NoAcknowledge(_) | Overrun | Framing | Parity | Noise | Crc
+            | Timeout | Interrupted => Class::Transient,
+            Unsupported | InvalidInput | NotFound | PermissionDenied | AddrInUse
+            | AddrNotAvailable | AlreadyExists | NotAligned | OutOfBounds => Class::Configuration,
+            ModeFault | ChipSelectFault | NotConnected | ConnectionRefused | ConnectionReset
+            | ConnectionAborted | BrokenPipe | OutOfMemory | WriteZero | InvalidData | Other => {
+                Class::Fatal
+            }
+        }
+    }
+}
+
+/// Classification of `ErrorKind`s
+///
+/// Implemented for all kinds that convert into [`HalErrorKind`].
+pub trait Classify {
+    /// Classify using the given policy
+    fn class_with<P: Policy>(&self) -> Class;
+
+    /// Classify using the [`DefaultPolicy`]
+    fn class(&self) -> Class {
+        self.class_with::<DefaultPolicy>()
+    }
+
+    /// Retrying may succeed
+    fn is_transient(&self) -> bool {
+        self.class() == Class::Transient
+    }
+
+    /// Can not succeed with the current configuration
+    fn is_configuration(&self) -> bool {
+        self.class() == Class::Configuration
+    }
+
+    /// Retrying will not help
+    fn is_fatal(&self) -> bool {
+        self.class() == Class::Fatal
+    }
+}
+
+impl<K: Copy + Into<HalErrorKind>> Classify for K {
+    fn class_with<P: Policy>(&self) -> Class {
+        P::classify((*self).into())
+    }
+}
+
+impl<E, K: Copy + Into<HalErrorKind>> Error<E, K> {
+    /// Classify the stored `ErrorKind` using the given policy
+    pub fn class_with<P: Policy>(&self) -> Class {
+        self.kind.class_with::<P>()
+    }
+
+    /// Classify the stored `ErrorKind` using the [`DefaultPolicy`]
+    pub fn class(&self) -> Class {
+        self.kind.class()
+    }
+
+    /// Retrying may succeed
+    pub fn is_transient(&self) -> bool {
+        self.kind.is_transient()
+    }
+
+    /// Can not succeed with the current configuration
+    pub fn is_configuration(&self) -> bool {
+        self.kind.is_configuration()
+    }
+
+    /// Retrying will not help
+    pub fn is_fatal(&self) -> bool {
+        self.kind.is_fatal()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "i2c", feature = "spi", feature = "io"))]
+    fn default() {
+        use embedded_hal::{i2c, spi};
+
+        assert!(i2c::ErrorKind::ArbitrationLoss.is_transient());
+        assert!(spi::ErrorKind::ModeFault.is_fatal());
+        assert!(embedded_io::ErrorKind::Interrupted.is_transient());
+        assert!(embedded_io::ErrorKind::Unsupported.is_configuration());
+        let err: crate::I2cError<_> = i2c::ErrorKind::Bus.into();
+        assert_eq!(err.class(), Class::Transient);
+    }
+
+    #[test]
+    fn policy() {
+        struct Strict;
+        impl Policy for Strict {
+            fn classify(kind: HalErrorKind) -> Class {
+                match kind {
+                    HalErrorKind::NoAcknowledge(_) => Class::Fatal,
+                    kind => DefaultPolicy::classify(kind),
+                }
+            }
+        }
+
+        let kind = HalErrorKind::NoAcknowledge(crate::NoAcknowledgeSource::Address);
+        assert_eq!(kind.class(), Class::Transient);
+        assert_eq!(kind.class_with::<Strict>(), Class::Fatal);
+        assert_eq!(
+            HalErrorKind::Timeout.class_with::<Strict>(),
+            Class::Transient
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Stable numeric error codes
+//!
+//! The high byte of a code is the family, the low byte the variant.
+//! Codes are frozen: existing entries never change, new upstream variants get new codes.
+//! Upstream variants without an entry encode as the family `Other`.
+
+use crate::{Error, Family, HalErrorKind, NoAcknowledgeSource};
+
+/// Entry of the code table
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CodeEntry {
+    /// Numeric code
+    pub code: u16,
+    /// Family name, see [`Family::NAME`]
+    pub family: &'static str,
+    /// Family `ErrorKind` variant name
+    pub variant: &'static str,
+    /// Unified kind
+    pub kind: HalErrorKind,
+}
+
+impl CodeEntry {
+    /// Look up a code
+    pub fn lookup(code: u16) -> Option<&'static Self> {
+        CODES.iter().find(|e| e.code == code)
+    }
+}
+
+macro_rules! codes {
+    ($(
+        $feature:literal $alias:ident $shim:ident:
+            $($mod:ident)::+, $error:ident, $kind:ident, $family:literal = $code:literal {
+            $(
+                $($name:literal)? $variant:ident
+                $(($arg:ident: $argty:ident {
+                    $($subname:literal $sub:ident = $subcode:literal),+ $(,)?
+                }))?
+                $(= $variant_code:literal)? => $hal:expr
+            ),+ $(,)?
+        }
+    )+) => {
+        /// Table of all codes
+        ///
+        /// Available independent of the enabled family features to support decoding.
+        pub const CODES: &[CodeEntry] = &[
+            $($(
+                $(CodeEntry {
+                    code: ($code << 8) | $variant_code,
+                    family: $family,
+                    variant: $name,
+                    kind: $hal,
+                },)?
+                $($(CodeEntry {
+                    code: ($code << 8) | $subcode,
+                    family: $family,
+                    variant: $subname,
+                    kind: $hal($argty::$sub),
+                },)+)?
+            )+)+
+            CodeEntry {
+                code: 0xff00,
+                family: "unknown",
+                variant: "Unknown",
+                kind: HalErrorKind::Other,
+            },
+        ];
+
+        $(
+            #[cfg(feature = $feature)]
+            impl Code for $($mod ::)+ $kind {
+                const FAMILY: u8 = $code;
+
+                fn variant(self) -> u8 {
+                    use $($mod)::+ as upstream;
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        $(
+                            $(upstream::$kind::$variant => $variant_code,)?
+                            $($(upstream::$kind::$variant(upstream::$argty::$sub) => $subcode,)+)?
+                        )+
+                        _ => 0x00,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+families!(codes);
+
+impl HalErrorKind {
+    /// Decode a numeric code, see [`Code`]
+    pub fn from_code(code: u16) -> Option<Self> {
+        CodeEntry::lookup(code).map(|e| e.kind)
+    }
+}
+
+/// Stable numeric code of an `ErrorKind`
+///
+/// Implemented for the `ErrorKind` of every supported family.
+/// Family codes `0x80..=0xfe` are reserved for custom families declared using
+/// [`family!`](crate::family), `0xff` is [`Unknown`](crate::Unknown).
+pub trait Code: Family {
+    /// Family code, the high byte
+    const FAMILY: u8;
+
+    /// Variant code, the low byte
+    fn variant(self) -> u8;
+
+    /// Numeric code
+    fn code(self) -> u16 {
+        ((Self::FAMILY as u16) << 8) | self.variant() as u16
+    }
+}
+
+impl<E, K: Code> Error<E, K> {
+    /// Stable numeric code of the stored `ErrorKind`
+    pub fn code(&self) -> u16 {
+        self.kind.code()
+    }
+}
+
+impl Code for crate::Unknown {
+    const FAMILY: u8 = 0xff;
+
+    fn variant(self) -> u8 {
+        0x00
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[allow(dead_code)]
+    fn check<K: Code + core::fmt::Debug>(map: &[(K, u16)]) {
+        extern crate std;
+
+        for (kind, code) in map {
+            assert_eq!(kind.code(), *code, "{kind:?}");
+            let entry = CodeEntry::lookup(*code).unwrap();
+            assert_eq!(entry.family, K::NAME);
+            assert_eq!(entry.variant, std::format!("{kind:?}"));
+            assert_eq!(entry.kind, (*kind).into());
+            assert_eq!(HalErrorKind::from_code(*code), Some((*kind).into()));
+        }
+        // Every table entry of the family is covered
+        let family = CODES.iter().filter(|e| e.family == K::NAME).count();
+        assert_eq!(family, map.len());
+    }
+
+    #[test]
+    fn table() {
+        for (i, a) in CODES.iter().enumerate() {
+            assert!(CODES[i + 1..].iter().all(|b| b.code != a.code));
+            assert!(CODES[i + 1..]
+                .iter()
+                .all(|b| b.family != a.family || b.variant != a.variant));
+        }
+        assert_eq!(HalErrorKind::from_code(0x0000), None);
+        assert_eq!(HalErrorKind::from_code(0x02ff), None);
+    }
+
+    #[test]
+    fn unknown() {
+        check(&[(crate::Unknown, 0xff00)]);
+    }
+
+    #[test]
+    #[cfg(feature = "digital")]
+    fn digital() {
+        use embedded_hal::digital::ErrorKind as K;
+        check(&[(K::Other, 0x0100)]);
+    }
+
+    #[test]
+    #[cfg(feature = "i2c")]
+    fn i2c() {
+        use embedded_hal::i2c::{ErrorKind as K, NoAcknowledgeSource as N};
+        check(&[
+            (K::Other, 0x0200),
+            (K::Bus, 0x0201),
+            (K::ArbitrationLoss, 0x0202),
+            (K::NoAcknowledge(N::Address), 0x0203),
+            (K::NoAcknowledge(N::Data), 0x0204),
+            (K::NoAcknowledge(N::Unknown), 0x0205),
+            (K::Overrun, 0x0206),
+        ]);
+        let err: crate::I2cError<_> = K::NoAcknowledge(N::Address).into();
+        assert_eq!(err.code(), 0x0203);
+    }
+
+    #[test]
+    #[cfg(feature = "pwm")]
+    fn pwm() {
+        use embedded_hal::pwm::ErrorKind as K;
+        check(&[(K::Other, 0x0300)]);
+    }
+
+    #[test]
+    #[cfg(feature = "spi")]
+    fn spi() {
+        use embedded_hal::spi::ErrorKind as K;
+        check(&[
+            (K::Other, 0x0400),
+            (K::Overrun, 0x0401),
+            (K::ModeFault, 0x0402),
+            (K::FrameFormat, 0x0403),
+            (K::ChipSelectFault, 0x0404),
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "can")]
+    fn can() {
+        use embedded_can::ErrorKind as K;
+        check(&[
+            (K::Other, 0x0500),
+            (K::Overrun, 0x0501),
+            (K::Bit, 0x0502),
+            (K::Stuff, 0x0503),
+            (K::Crc, 0x0504),
+            (K::Form, 0x0505),
+            (K::Acknowledge, 0x0506),
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "serial-nb")]
+    fn serial() {
+        use embedded_hal_nb::serial::ErrorKind as K;
+        check(&[
+            (K::Other, 0x0600),
+            (K::Overrun, 0x0601),
+            (K::FrameFormat, 0x0602),
+            (K::Parity, 0x0603),
+            (K::Noise, 0x0604),
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "io")]
+    fn io() {
+        use embedded_io::ErrorKind as K;
+        check(&[
+            (K::Other, 0x0700),
+            (K::NotFound, 0x0701),
+            (K::PermissionDenied, 0x0702),
+            (K::ConnectionRefused, 0x0703),
+            (K::ConnectionReset, 0x0704),
+            (K::ConnectionAborted, 0x0705),
+            (K::NotConnected, 0x0706),
+            (K::AddrInUse, 0x0707),
+            (K::AddrNotAvailable, 0x0708),
+            (K::BrokenPipe, 0x0709),
+            (K::AlreadyExists, 0x070a),
+            (K::InvalidInput, 0x070b),
+            (K::InvalidData, 0x070c),
+            (K::TimedOut, 0x070d),
+            (K::Interrupted, 0x070e),
+            (K::Unsupported, 0x070f),
+            (K::OutOfMemory, 0x0710),
+            (K::WriteZero, 0x0711),
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "nor-flash")]
+    fn nor_flash() {
+        use embedded_storage::nor_flash::NorFlashErrorKind as K;
+        check(&[
+            (K::Other, 0x0800),
+            (K::NotAligned, 0x0801),
+            (K::OutOfBounds, 0x0802),
+        ]);
+    }
+}
@@ -0,0 +1,168 @@
+//! Attach context to HAL errors
+
+use core::{error, fmt};
+
+use crate::Error;
+
+/// [`Error`] with a static description of the failed operation
+///
+/// Displays the context only. The wrapped [`Error`] is the
+/// [`core::error::Error::source()`].
+pub struct Context<E, K> {
+    context: &'static str,
+    error: Error<E, K>,
+}
+
+impl<E, K> Context<E, K> {
+    /// The attached context
+    pub fn context(&self) -> &'static str {
+        self.context
+    }
+
+    /// Extract the inner [`Error`]
+    pub fn into_inner(self) -> Error<E, K> {
+        self.error
+    }
+}
+
+impl<E, K> core::ops::Deref for Context<E, K> {
+    type Target = Error<E, K>;
+    fn deref(&self) -> &Self::Target {
+        &self.error
+    }
+}
+
+impl<E, K> fmt::Display for Context<E, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.context)
+    }
+}
+
+impl<E: fmt::Debug, K> fmt::Debug for Context<E, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("context", &self.context)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<E, K> defmt::Format for Context<E, K>
+where
+    Error<E, K>: defmt::Format,
+{
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{=str}: {}", self.context, self.error)
+    }
+}
+
+impl<E: fmt::Debug + 'static, K: error::Error + 'static> error::Error for Context<E, K> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Attach context to a `Result` with a HAL error
+///
+/// The error is wrapped in [`Error`] and then in [`Context`].
+/// With the `location` feature the caller is recorded as the location of the [`Error`].
+pub trait ResultExt<T, E> {
+    /// Attach a static context
+    fn context<K>(self, context: &'static str) -> Result<T, Context<E, K>>
+    where
+        Error<E, K>: From<E>;
+
+    /// Attach a lazily evaluated static context
+    fn with_context<K>(self, context: impl FnOnce() -> &'static str) -> Result<T, Context<E, K>>
+    where
+        Error<E, K>: From<E>;
+}
+
+impl<T, E> ResultExt<T, E> for Result<T, E> {
+    #[track_caller]
+    fn context<K>(self, context: &'static str) -> Result<T, Context<E, K>>
+    where
+        Error<E, K>: From<E>,
+    {
+        self.with_context(|| context)
+    }
+
+    #[track_caller]
+    fn with_context<K>(self, context: impl FnOnce() -> &'static str) -> Result<T, Context<E, K>>
+    where
+        Error<E, K>: From<E>,
+    {
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(Context {
+                context: context(),
+                error: Error::from(err),
+            }),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "i2c"))]
+mod tests {
+    use core::error::Error as _;
+    use embedded_hal::i2c::{self, ErrorKind};
+
+    use super::{Context, ResultExt};
+
+    #[derive(Debug)]
+    struct Error;
+    impl i2c::Error for Error {
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Bus
+        }
+    }
+
+    fn read() -> Result<u8, Error> {
+        Err(Error)
+    }
+
+    fn who_am_i() -> Result<u8, Context<Error, ErrorKind>> {
+        read().context("reading WHO_AM_I")
+    }
+
+    #[test]
+    fn inspect() {
+        extern crate std;
+        use std::string::ToString;
+
+        let err = who_am_i().unwrap_err();
+        assert_eq!(err.to_string(), "reading WHO_AM_I");
+        assert_eq!(err.context(), "reading WHO_AM_I");
+        assert_eq!(err.hal_kind(), crate::HalErrorKind::Bus); // Deref
+        let err_dyn = err.source().unwrap();
+        let _: &crate::I2cError<Error> = err_dyn.downcast_ref().unwrap();
+        let kind: &ErrorKind = err_dyn.source().unwrap().downcast_ref().unwrap();
+        assert!(matches!(kind, ErrorKind::Bus));
+    }
+
+    #[test]
+    #[cfg(feature = "location")]
+    fn location() {
+        let err: Context<Error, ErrorKind> = read().context("reading WHO_AM_I").unwrap_err();
+        assert_eq!(err.location().file(), file!());
+        assert_eq!(err.location().line(), line!() - 2);
+        let err: Context<Error, ErrorKind> = read().with_context(|| "flushing FIFO").unwrap_err();
+        assert_eq!(err.location().line(), line!() - 1);
+    }
+
+    #[test]
+    fn lazy() {
+        let mut called = false;
+        let ok: Result<_, Context<Error, ErrorKind>> = Ok::<_, Error>(1).with_context(|| {
+            called = true;
+            "unused"
+        });
+        assert_eq!(ok.unwrap(), 1);
+        assert!(!called);
+        // The `ErrorKind` is inferred from the family of the error
+        let err = read().with_context(|| "flushing FIFO").unwrap_err();
+        assert_eq!(err.context(), "flushing FIFO");
+        assert_eq!(err.hal_kind(), crate::HalErrorKind::Bus);
+    }
+}
@@ -0,0 +1,198 @@
+//! Ready-made driver error
+
+use core::{convert::Infallible, error, fmt};
+
+use crate::{Class, Classify, Error, HalErrorKind};
+
+/// Error of a driver using a bus, pins, and a timeout
+///
+/// `BUS` and `PIN` are [`Error`]s, e.g. `I2cError<I::Error>` and
+/// `DigitalError<P::Error>`. `C` is the driver specific error.
+///
+/// The raw bus error converts using `?`. Pin errors would overlap with that
+/// and are converted explicitly, e.g. using `.map_err(DriverError::pin)?`.
+/// With the `location` feature that records a location inside `core`,
+/// see [`Error`]. Use a `match` calling [`DriverError::pin()`] to record the call site.
+///
+/// [`core::error::Error::source()`] is the wrapped bus, pin or driver specific error.
+#[derive(Debug)]
+pub enum DriverError<BUS, PIN, C = Infallible> {
+    /// Bus error
+    Bus(BUS),
+    /// Pin error
+    Pin(PIN),
+    /// Operation timed out
+    Timeout,
+    /// Driver specific error
+    Custom(C),
+}
+
+impl<BUS, E, K, C> DriverError<BUS, Error<E, K>, C> {
+    /// Wrap a raw pin error
+    #[track_caller]
+    pub fn pin(err: E) -> Self
+    where
+        Error<E, K>: From<E>,
+    {
+        Self::Pin(Error::from(err))
+    }
+}
+
+impl<BE, BK, PE, PK, C> DriverError<Error<BE, BK>, Error<PE, PK>, C>
+where
+    BK: Copy + Into<HalErrorKind>,
+    PK: Copy + Into<HalErrorKind>,
+{
+    /// The unified kind of the bus or pin error or [`HalErrorKind::Timeout`]
+    ///
+    /// `None` for driver specific errors.
+    pub fn hal_kind(&self) -> Option<HalErrorKind> {
+        match self {
+            Self::Bus(e) => Some(e.hal_kind()),
+            Self::Pin(e) => Some(e.hal_kind()),
+            Self::Timeout => Some(HalErrorKind::Timeout),
+            Self::Custom(_) => None,
+        }
+    }
+
+    /// Classify the unified kind using the [`DefaultPolicy`](crate::DefaultPolicy)
+    ///
+    /// `None` for driver specific errors.
+    pub fn class(&self) -> Option<Class> {
+        self.hal_kind().map(|kind| kind.class())
+    }
+}
+
+impl<E, K, PIN, C> From<E> for DriverError<Error<E, K>, PIN, C>
+where
+    Error<E, K>: From<E>,
+{
+    #[track_caller]
+    fn from(value: E) -> Self {
+        Self::Bus(Error::from(value))
+    }
+}
+
+impl<E, K, PIN, C> From<Error<E, K>> for DriverError<Error<E, K>, PIN, C> {
+    fn from(value: Error<E, K>) -> Self {
+        Self::Bus(value)
+    }
+}
+
+impl<BUS: fmt::Display, PIN: fmt::Display, C: fmt::Display> fmt::Display
+    for DriverError<BUS, PIN, C>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bus(e) => write!(f, "bus error: {e}"),
+            Self::Pin(e) => write!(f, "pin error: {e}"),
+            Self::Timeout => write!(f, "timeout"),
+            Self::Custom(e) => write!(f, "driver error: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<BUS: defmt::Format, PIN: defmt::Format, C: defmt::Format> defmt::Format
+    for DriverError<BUS, PIN, C>
+{
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            Self::Bus(e) => defmt::write!(f, "bus error: {}", e),
+            Self::Pin(e) => defmt::write!(f, "pin error: {}", e),
+            Self::Timeout => defmt::write!(f, "timeout"),
+            Self::Custom(e) => defmt::write!(f, "driver error: {}", e),
+        }
+    }
+}
+
+impl<BUS, PIN, C> error::Error for DriverError<BUS, PIN, C>
+where
+    BUS: error::Error + 'static,
+    PIN: error::Error + 'static,
+    C: error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Bus(e) => Some(e),
+            Self::Pin(e) => Some(e),
+            Self::Timeout => None,
+            Self::Custom(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "i2c", feature = "digital"))]
+mod tests {
+    use core::{error::Error as _, fmt};
+    use embedded_hal::{digital, i2c};
+
+    use super::DriverError;
+    use crate::{Class, DigitalError, HalErrorKind, I2cError};
+
+    #[derive(Debug)]
+    struct BusError;
+    impl i2c::Error for BusError {
+        fn kind(&self) -> i2c::ErrorKind {
+            i2c::ErrorKind::ArbitrationLoss
+        }
+    }
+
+    #[derive(Debug)]
+    struct PinError;
+    impl digital::Error for PinError {
+        fn kind(&self) -> digital::ErrorKind {
+            digital::ErrorKind::Other
+        }
+    }
+
+    #[derive(Debug)]
+    struct ChipId(u8);
+    impl fmt::Display for ChipId {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "invalid chip ID {:#x}", self.0)
+        }
+    }
+    impl core::error::Error for ChipId {}
+
+    type Error = DriverError<I2cError<BusError>, DigitalError<PinError>, ChipId>;
+
+    fn read() -> Result<u8, BusError> {
+        Err(BusError)
+    }
+
+    fn set_high() -> Result<(), PinError> {
+        Err(PinError)
+    }
+
+    #[test]
+    fn bus() {
+        let err = (|| -> Result<_, Error> { Ok(read()?) })().unwrap_err();
+        assert!(matches!(err, DriverError::Bus(_)));
+        assert_eq!(err.hal_kind(), Some(HalErrorKind::ArbitrationLoss));
+        assert_eq!(err.class(), Some(Class::Transient));
+        let _: &I2cError<BusError> = err.source().unwrap().downcast_ref().unwrap();
+    }
+
+    #[test]
+    fn pin() {
+        let err: Error = set_high().map_err(DriverError::pin).unwrap_err();
+        assert_eq!(err.hal_kind(), Some(HalErrorKind::Other));
+        let _: &DigitalError<PinError> = err.source().unwrap().downcast_ref().unwrap();
+    }
+
+    #[test]
+    fn other() {
+        extern crate std;
+        use std::string::ToString;
+
+        let err = Error::Timeout;
+        assert_eq!(err.hal_kind(), Some(HalErrorKind::Timeout));
+        assert!(err.source().is_none());
+        let err = Error::Custom(ChipId(0x42));
+        assert_eq!(err.hal_kind(), None);
+        assert_eq!(err.class(), None);
+        assert_eq!(err.to_string(), "driver error: invalid chip ID 0x42");
+        let _: &ChipId = err.source().unwrap().downcast_ref().unwrap();
+    }
+}
@@ -0,0 +1,172 @@
+//! POSIX `errno` mapping
+//!
+//! Values are those of Linux. The mapping of the I2C kinds follows the Linux
+//! I2C fault code conventions (`Documentation/i2c/fault-codes.rst`).
+
+use crate::{Error, HalErrorKind, NoAcknowledgeSource};
+
+const EPERM: i32 = 1;
+const ENOENT: i32 = 2;
+const EINTR: i32 = 4;
+const EIO: i32 = 5;
+const ENXIO: i32 = 6;
+const EAGAIN: i32 = 11;
+const ENOMEM: i32 = 12;
+const EACCES: i32 = 13;
+const EFAULT: i32 = 14;
+const EBUSY: i32 = 16;
+const EEXIST: i32 = 17;
+const EINVAL: i32 = 22;
+const ENOSPC: i32 = 28;
+const EPIPE: i32 = 32;
+const EPROTO: i32 = 71;
+const EBADMSG: i32 = 74;
+const EOVERFLOW: i32 = 75;
+const EOPNOTSUPP: i32 = 95;
+const EADDRINUSE: i32 = 98;
+const EADDRNOTAVAIL: i32 = 99;
+const ECONNABORTED: i32 = 103;
+const ECONNRESET: i32 = 104;
+const ENOTCONN: i32 = 107;
+const ETIMEDOUT: i32 = 110;
+const ECONNREFUSED: i32 = 111;
+const EREMOTEIO: i32 = 121;
+
+impl HalErrorKind {
+    /// Map to a positive POSIX `errno` value
+    pub fn errno(self) -> i32 {
+        match self {
+            Self::Bus | Self::ChipSelectFault | Self::Other => EIO,
+            Self::ArbitrationLoss => EAGAIN,
+            Self::NoAcknowledge(NoAcknowledgeSource::Address) => ENXIO,
+            Self::NoAcknowledge(_) => EREMOTEIO,
+            Self::Overrun => EOVERFLOW,
+            Self::Framing | Self::Parity | Self::Noise => EPROTO,
+            Self::Crc | Self::InvalidData => EBADMSG,
+            Self::ModeFault => EBUSY,
+            Self::Timeout => ETIMEDOUT,
+            Self::Interrupted => EINTR,
+            Self::Unsupported => EOPNOTSUPP,
+            Self::InvalidInput | Self::NotAligned => EINVAL,
+            Self::NotFound => ENOENT,
+            Self::PermissionDenied => EACCES,
+            Self::NotConnected => ENOTCONN,
+            Self::ConnectionRefused => ECONNREFUSED,
+            Self::ConnectionReset => ECONNRESET,
+            Self::ConnectionAborted => ECONNABORTED,
+            Self::AddrInUse => EADDRINUSE,
+            Self::AddrNotAvailable => EADDRNOTAVAIL,
+            Self::BrokenPipe => EPIPE,
+            Self::AlreadyExists => EEXIST,
+            Self::OutOfMemory => ENOMEM,
+            Self::WriteZero => ENOSPC,
+            Self::OutOfBounds => EFAULT,
+        }
+    }
+
+    /// Map a positive POSIX `errno` value to the closest kind
+    ///
+    /// Values shared by several kinds map to the most generic one.
+    /// `EAGAIN` is a retry condition and maps to [`HalErrorKind::Interrupted`].
+    /// Returns `None` for values without a corresponding kind.
+    pub fn from_errno(errno: i32) -> Option<Self> {
+        Some(match errno {
+            EIO => Self::Other,
+            EAGAIN => Self::Interrupted,
+            ENXIO => Self::NoAcknowledge(NoAcknowledgeSource::Address),
+            EREMOTEIO => Self::NoAcknowledge(NoAcknowledgeSource::Data),
+            EOVERFLOW => Self::Overrun,
+            EPROTO => Self::Framing,
+            EBADMSG => Self::InvalidData,
+            EBUSY => Self::ModeFault,
+            ETIMEDOUT => Self::Timeout,
+            EINTR => Self::Interrupted,
+            EOPNOTSUPP => Self::Unsupported,
+            EINVAL => Self::InvalidInput,
+            ENOENT => Self::NotFound,
+            EACCES | EPERM => Self::PermissionDenied,
+            ENOTCONN => Self::NotConnected,
+            ECONNREFUSED => Self::ConnectionRefused,
+            ECONNRESET => Self::ConnectionReset,
+            ECONNABORTED => Self::ConnectionAborted,
+            EADDRINUSE => Self::AddrInUse,
+            EADDRNOTAVAIL => Self::AddrNotAvailable,
+            EPIPE => Self::BrokenPipe,
+            EEXIST => Self::AlreadyExists,
+            ENOMEM => Self::OutOfMemory,
+            ENOSPC => Self::WriteZero,
+            EFAULT => Self::OutOfBounds,
+            _ => return None,
+        })
+    }
+}
+
+impl<E, K: Copy + Into<HalErrorKind>> Error<E, K> {
+    /// Map the stored `ErrorKind` to a positive POSIX `errno` value
+    pub fn errno(&self) -> i32 {
+        self.hal_kind().errno()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HalErrorKind as H, NoAcknowledgeSource as S};
+
+    #[test]
+    fn table() {
+        for (kind, errno, back) in [
+            (H::Bus, 5, H::Other),
+            (H::ArbitrationLoss, 11, H::Interrupted),
+            (
+                H::NoAcknowledge(S::Address),
+                6,
+                H::NoAcknowledge(S::Address),
+            ),
+            (H::NoAcknowledge(S::Data), 121, H::NoAcknowledge(S::Data)),
+            (H::NoAcknowledge(S::Unknown), 121, H::NoAcknowledge(S::Data)),
+            (H::Overrun, 75, H::Overrun),
+            (H::Framing, 71, H::Framing),
+            (H::Parity, 71, H::Framing),
+            (H::Noise, 71, H::Framing),
+            (H::Crc, 74, H::InvalidData),
+            (H::ModeFault, 16, H::ModeFault),
+            (H::ChipSelectFault, 5, H::Other),
+            (H::Timeout, 110, H::Timeout),
+            (H::Interrupted, 4, H::Interrupted),
+            (H::Unsupported, 95, H::Unsupported),
+            (H::InvalidInput, 22, H::InvalidInput),
+            (H::InvalidData, 74, H::InvalidData),
+            (H::NotFound, 2, H::NotFound),
+            (H::PermissionDenied, 13, H::PermissionDenied),
+            (H::NotConnected, 107, H::NotConnected),
+            (H::ConnectionRefused, 111, H::ConnectionRefused),
+            (H::ConnectionReset, 104, H::ConnectionReset),
+            (H::ConnectionAborted, 103, H::ConnectionAborted),
+            (H::AddrInUse, 98, H::AddrInUse),
+            (H::AddrNotAvailable, 99, H::AddrNotAvailable),
+            (H::BrokenPipe, 32, H::BrokenPipe),
+            (H::AlreadyExists, 17, H::AlreadyExists),
+            (H::OutOfMemory, 12, H::OutOfMemory),
+            (H::WriteZero, 28, H::WriteZero),
+            (H::NotAligned, 22, H::InvalidInput),
+            (H::OutOfBounds, 14, H::OutOfBounds),
+            (H::Other, 5, H::Other),
+        ] {
+            assert_eq!(kind.errno(), errno, "{kind:?}");
+            assert_eq!(H::from_errno(errno), Some(back), "{errno}");
+        }
+        assert_eq!(H::from_errno(1), Some(H::PermissionDenied));
+        // Userspace drivers report would-block as `EAGAIN`, not arbitration loss
+        assert_eq!(H::from_errno(11), Some(H::Interrupted));
+        assert_eq!(H::from_errno(0), None);
+    }
+
+    #[test]
+    #[cfg(feature = "i2c")]
+    fn i2c() {
+        use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+
+        let err: crate::I2cError<_> = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address).into();
+        assert_eq!(err.errno(), 6);
+    }
+}
@@ -0,0 +1,132 @@
+//! Custom HAL families
+
+/// Declare a custom HAL family
+///
+/// For a family `Error` trait with a `kind()` method returning a family `ErrorKind`
+/// this declares a type alias for [`Error`](crate::Error) and implements:
+///
+/// * [`KindOf`](crate::KindOf) for the alias, providing `From<E>`
+/// * the family `Error` trait for the alias
+/// * [`Family`](crate::Family) for the `ErrorKind`, with the given family name
+/// * [`Code`](crate::Code) for the `ErrorKind`, if a code table is given.
+///   Unlisted variants have variant code `0x00`.
+///   The family code must be in the custom range `0x80..=0xfe`, checked at compile time.
+///
+/// The `Error` trait and the `ErrorKind` must be defined in the invoking crate.
+/// The `ErrorKind` must implement `Copy`, `Into<HalErrorKind>`
+/// (for classification), and `core::error::Error` (for the source).
+///
+/// Custom families are not known to the rest of this crate:
+///
+/// * [`HalErrorKind::find()`](crate::HalErrorKind::find) does not find their `ErrorKind`
+///   in a source chain. Use `downcast_ref()` on the source instead.
+/// * [`HalErrorKind::from_code()`](crate::HalErrorKind::from_code), [`CODES`](crate::CODES)
+///   and `ehe-decode` do not decode their codes.
+/// * There is no [`SerdeKind`](crate::SerdeKind) for their `ErrorKind`.
+///
+/// ```
+/// # use core::fmt;
+/// # use embedded_hal_error::HalErrorKind;
+/// pub trait Error: fmt::Debug {
+///     fn kind(&self) -> ErrorKind;
+/// }
+///
+/// #[derive(Debug, Copy, Clone)]
+/// pub enum ErrorKind {
+///     Overrange,
+///     Other,
+/// }
+/// # impl fmt::Display for ErrorKind {
+/// #     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// #         fmt::Debug::fmt(self, f)
+/// #     }
+/// # }
+/// # impl core::error::Error for ErrorKind {}
+/// # impl From<ErrorKind> for HalErrorKind {
+/// #     fn from(value: ErrorKind) -> Self {
+/// #         match value {
+/// #             ErrorKind::Overrange => Self::OutOfBounds,
+/// #             ErrorKind::Other => Self::Other,
+/// #         }
+/// #     }
+/// # }
+///
+/// embedded_hal_error::family!(
+///     /// ADC errors
+///     pub type AdcError = Error => ErrorKind, "adc", code 0x80 {
+///         ErrorKind::Overrange => 0x01,
+///     }
+/// );
+/// ```
+///
+/// Family codes outside of the custom range are rejected:
+///
+/// ```compile_fail
+/// # use core::fmt;
+/// # use embedded_hal_error::HalErrorKind;
+/// # pub trait Error: fmt::Debug {
+/// #     fn kind(&self) -> ErrorKind;
+/// # }
+/// # #[derive(Debug, Copy, Clone)]
+/// # pub struct ErrorKind;
+/// # impl fmt::Display for ErrorKind {
+/// #     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// #         fmt::Debug::fmt(self, f)
+/// #     }
+/// # }
+/// # impl core::error::Error for ErrorKind {}
+/// # impl From<ErrorKind> for HalErrorKind {
+/// #     fn from(value: ErrorKind) -> Self {
+/// #         Self::Other
+/// #     }
+/// # }
+/// embedded_hal_error::family!(
+///     pub type AdcError = Error => ErrorKind, "adc", code 0x02 {}
+/// );
+/// ```
+#[macro_export]
+macro_rules! family {
+    (
+        $(#[$meta:meta])*
+        $vis:vis type $alias:ident = $error:path => $kind:ty, $name:literal
+        $(, code $family:literal { $($variant:pat => $code:literal),* $(,)? })? $(,)?
+    ) => {
+        $(#[$meta])*
+        $vis type $alias<E> = $crate::Error<E, $kind>;
+
+        impl<E: $error> $crate::KindOf<$kind, E> for $crate::Error<E, $kind> {
+            fn kind_of(error: &E) -> $kind {
+                <E as $error>::kind(error)
+            }
+        }
+
+        impl<E: $error> $error for $crate::Error<E, $kind> {
+            fn kind(&self) -> $kind {
+                <E as $error>::kind(self)
+            }
+        }
+
+        impl $crate::Family for $kind {
+            const NAME: &'static str = $name;
+        }
+
+        $(
+            const _: () = assert!(
+                0x80 <= $family && $family <= 0xfe,
+                "custom family codes must be in 0x80..=0xfe"
+            );
+
+            impl $crate::Code for $kind {
+                const FAMILY: u8 = $family;
+
+                fn variant(self) -> u8 {
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        $($variant => $code,)*
+                        _ => 0x00,
+                    }
+                }
+            }
+        )?
+    };
+}
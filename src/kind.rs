@@ -0,0 +1,350 @@
+//! Unified error kind across HAL families
+
+use core::{error, fmt};
+
+/// Unified `ErrorKind` across all HAL families
+///
+/// Every supported family `ErrorKind` converts into this using `From`.
+/// Family specific details that have no common meaning map to [`HalErrorKind::Other`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum HalErrorKind {
+    /// Bus error, e.g. a misplaced start/stop condition or a CAN bit error
+    Bus,
+    /// Arbitration was lost to another bus controller
+    ArbitrationLoss,
+    /// No acknowledge was received
+    NoAcknowledge(NoAcknowledgeSource),
+    /// The peripheral receive buffer was overrun
+    Overrun,
+    /// Received data does not conform to the expected frame format
+    Framing,
+    /// Parity check failed
+    Parity,
+    /// Noise was detected on the line
+    Noise,
+    /// Checksum mismatch
+    Crc,
+    /// Multiple devices are trying to drive the SPI slave select pin
+    ModeFault,
+    /// Asserting or deasserting the chip select pin failed
+    ChipSelectFault,
+    /// The operation timed out
+    Timeout,
+    /// The operation was interrupted
+    Interrupted,
+    /// The operation is not supported
+    Unsupported,
+    /// A parameter was incorrect
+    InvalidInput,
+    /// Invalid data was encountered
+    InvalidData,
+    /// An entity was not found
+    NotFound,
+    /// The operation lacked the necessary privileges
+    PermissionDenied,
+    /// Not connected
+    NotConnected,
+    /// The connection was refused by the remote
+    ConnectionRefused,
+    /// The connection was reset by the remote
+    ConnectionReset,
+    /// The connection was aborted by the remote
+    ConnectionAborted,
+    /// The address is already in use
+    AddrInUse,
+    /// The address is not available
+    AddrNotAvailable,
+    /// The pipe was closed
+    BrokenPipe,
+    /// An entity already exists
+    AlreadyExists,
+    /// Memory allocation failed
+    OutOfMemory,
+    /// No data could be written
+    WriteZero,
+    /// The arguments are not properly aligned
+    NotAligned,
+    /// The arguments are out of bounds
+    OutOfBounds,
+    /// A different error occurred
+    Other,
+}
+
+/// Source of a missing acknowledge
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NoAcknowledgeSource {
+    /// The device did not acknowledge its address
+    Address,
+    /// The device did not acknowledge data
+    Data,
+    /// The source is unknown
+    Unknown,
+}
+
+/// HAL family `ErrorKind`
+///
+/// Implemented for the `ErrorKind` of every supported family.
+/// Use [`family!`](crate::family) to add custom families.
+pub trait Family: Copy + Into<HalErrorKind> {
+    /// Family name, e.g. `"i2c"`
+    const NAME: &'static str;
+
+    /// Format an [`Error`](crate::Error) of this family with inner `Error` `inner`
+    #[cfg(feature = "defmt")]
+    fn format<E: defmt::Format>(self, inner: &E, f: defmt::Formatter<'_>) {
+        let kind: HalErrorKind = self.into();
+        defmt::write!(f, "{=str}: {} <- {}", Self::NAME, kind, inner)
+    }
+}
+
+/// Family `ErrorKind` `K` of a HAL `Error` `E`
+///
+/// Implemented for [`Error<E, K>`](crate::Error) to provide `From<E>`.
+pub trait KindOf<K, E> {
+    /// The `ErrorKind` of `error`
+    fn kind_of(error: &E) -> K;
+}
+
+impl fmt::Display for NoAcknowledgeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl fmt::Display for HalErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl error::Error for HalErrorKind {}
+
+impl HalErrorKind {
+    /// Find the first `ErrorKind` of any supported family in a source chain
+    ///
+    /// Walks `error` and its [`core::error::Error::source()`] chain and returns the
+    /// first family `ErrorKind` (or `HalErrorKind`) found, converted to the unified kind.
+    pub fn find(error: &(dyn error::Error + 'static)) -> Option<Self> {
+        core::iter::successors(Some(error), |e| e.source()).find_map(Self::downcast)
+    }
+
+    fn downcast(error: &(dyn error::Error + 'static)) -> Option<Self> {
+        macro_rules! downcast {
+            ($(
+                $feature:literal $alias:ident $shim:ident:
+                    $($mod:ident)::+, $error:ident, $kind:ident, $name:literal = $code:literal
+                    $variants:tt
+            )+) => {
+                $(
+                    #[cfg(feature = $feature)]
+                    if let Some(kind) = error.downcast_ref::<$($mod ::)+ $kind>() {
+                        return Some((*kind).into());
+                    }
+                )+
+            };
+        }
+        families!(downcast);
+        #[cfg(feature = "serial-nb")]
+        if let Some(kind) = error.downcast_ref::<crate::SerialTimeoutKind>() {
+            return Some((*kind).into());
+        }
+        if let Some(kind) = error.downcast_ref::<crate::Unknown>() {
+            return Some((*kind).into());
+        }
+        error.downcast_ref::<Self>().copied()
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl From<embedded_hal::i2c::NoAcknowledgeSource> for NoAcknowledgeSource {
+    fn from(value: embedded_hal::i2c::NoAcknowledgeSource) -> Self {
+        use embedded_hal::i2c::NoAcknowledgeSource as S;
+        match value {
+            S::Address => Self::Address,
+            S::Data => Self::Data,
+            S::Unknown => Self::Unknown,
+        }
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl From<NoAcknowledgeSource> for embedded_hal::i2c::NoAcknowledgeSource {
+    fn from(value: NoAcknowledgeSource) -> Self {
+        match value {
+            NoAcknowledgeSource::Address => Self::Address,
+            NoAcknowledgeSource::Data => Self::Data,
+            NoAcknowledgeSource::Unknown => Self::Unknown,
+        }
+    }
+}
+
+macro_rules! impl_hal_kind {
+    ($(
+        $feature:literal $alias:ident $shim:ident:
+            $($mod:ident)::+, $error:ident, $kind:ident, $family:literal = $code:literal {
+            $(
+                $($name:literal)? $variant:ident
+                $(($arg:ident: $argty:ident {
+                    $($subname:literal $sub:ident = $subcode:literal),+ $(,)?
+                }))?
+                $(= $variant_code:literal)? => $hal:expr
+            ),+ $(,)?
+        }
+    )+) => {$(
+        #[cfg(feature = $feature)]
+        impl From<$($mod ::)+ $kind> for HalErrorKind {
+            fn from(value: $($mod ::)+ $kind) -> Self {
+                use $($mod ::)+ $kind as K;
+                #[allow(unreachable_patterns)]
+                match value {
+                    $(K::$variant $(($arg))? => $hal $(($arg.into()))?,)+
+                    _ => Self::Other,
+                }
+            }
+        }
+    )+};
+}
+
+families!(impl_hal_kind);
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::{HalErrorKind as H, NoAcknowledgeSource as S};
+
+    #[allow(dead_code)]
+    fn check<K: Copy + Into<H>>(map: &[(K, H)]) {
+        for (kind, hal) in map {
+            assert_eq!((*kind).into(), *hal);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "digital")]
+    fn digital() {
+        use embedded_hal::digital::ErrorKind as K;
+        check(&[(K::Other, H::Other)]);
+    }
+
+    #[test]
+    #[cfg(feature = "i2c")]
+    fn i2c() {
+        use embedded_hal::i2c::{ErrorKind as K, NoAcknowledgeSource as N};
+        check(&[
+            (K::Bus, H::Bus),
+            (K::ArbitrationLoss, H::ArbitrationLoss),
+            (K::NoAcknowledge(N::Address), H::NoAcknowledge(S::Address)),
+            (K::NoAcknowledge(N::Data), H::NoAcknowledge(S::Data)),
+            (K::NoAcknowledge(N::Unknown), H::NoAcknowledge(S::Unknown)),
+            (K::Overrun, H::Overrun),
+            (K::Other, H::Other),
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "i2c")]
+    fn hal_kind() {
+        use embedded_hal::i2c::{self, ErrorKind as K};
+
+        #[derive(Debug)]
+        struct Error;
+        impl i2c::Error for Error {
+            fn kind(&self) -> K {
+                K::ArbitrationLoss
+            }
+        }
+
+        let err = crate::I2cError::from(Error);
+        assert_eq!(err.hal_kind(), H::ArbitrationLoss);
+    }
+
+    #[test]
+    #[cfg(feature = "pwm")]
+    fn pwm() {
+        use embedded_hal::pwm::ErrorKind as K;
+        check(&[(K::Other, H::Other)]);
+    }
+
+    #[test]
+    #[cfg(feature = "spi")]
+    fn spi() {
+        use embedded_hal::spi::ErrorKind as K;
+        check(&[
+            (K::Overrun, H::Overrun),
+            (K::ModeFault, H::ModeFault),
+            (K::FrameFormat, H::Framing),
+            (K::ChipSelectFault, H::ChipSelectFault),
+            (K::Other, H::Other),
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "can")]
+    fn can() {
+        use embedded_can::ErrorKind as K;
+        check(&[
+            (K::Overrun, H::Overrun),
+            (K::Bit, H::Bus),
+            (K::Stuff, H::Framing),
+            (K::Crc, H::Crc),
+            (K::Form, H::Framing),
+            (K::Acknowledge, H::NoAcknowledge(S::Unknown)),
+            (K::Other, H::Other),
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "serial-nb")]
+    fn serial() {
+        use embedded_hal_nb::serial::ErrorKind as K;
+        check(&[
+            (K::Overrun, H::Overrun),
+            (K::FrameFormat, H::Framing),
+            (K::Parity, H::Parity),
+            (K::Noise, H::Noise),
+            (K::Other, H::Other),
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "io")]
+    fn io() {
+        use embedded_io::ErrorKind as K;
+        check(&[
+            (K::Other, H::Other),
+            (K::NotFound, H::NotFound),
+            (K::PermissionDenied, H::PermissionDenied),
+            (K::ConnectionRefused, H::ConnectionRefused),
+            (K::ConnectionReset, H::ConnectionReset),
+            (K::ConnectionAborted, H::ConnectionAborted),
+            (K::NotConnected, H::NotConnected),
+            (K::AddrInUse, H::AddrInUse),
+            (K::AddrNotAvailable, H::AddrNotAvailable),
+            (K::BrokenPipe, H::BrokenPipe),
+            (K::AlreadyExists, H::AlreadyExists),
+            (K::InvalidInput, H::InvalidInput),
+            (K::InvalidData, H::InvalidData),
+            (K::TimedOut, H::Timeout),
+            (K::Interrupted, H::Interrupted),
+            (K::Unsupported, H::Unsupported),
+            (K::OutOfMemory, H::OutOfMemory),
+            (K::WriteZero, H::WriteZero),
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "nor-flash")]
+    fn nor_flash() {
+        use embedded_storage::nor_flash::NorFlashErrorKind as K;
+        check(&[
+            (K::NotAligned, H::NotAligned),
+            (K::OutOfBounds, H::OutOfBounds),
+            (K::Other, H::Other),
+        ]);
+    }
+}
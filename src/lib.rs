@@ -5,23 +5,178 @@
 #![forbid(unsafe_code)]
 
 //! Provide `core::error::Error` for `embedded-hal` Errors using a newtype wrapper.
+//!
+//! Each HAL family is gated behind its own cargo feature
+//! (all enabled by default except `nor-flash`):
+//!
+//! * `digital`, `i2c`, `pwm`, `spi`: `embedded-hal`
+//! * `can`: `embedded-can`
+//! * `serial-nb`: `embedded-hal-nb`, with [`NbResultExt`] and [`block_timeout()`] for `nb::Result`
+//! * `io`: `embedded-io`
+//! * `nor-flash`: `embedded-storage`
+//!
+//! Custom families are declared using [`family!`].
+//! Errors without an `ErrorKind` (e.g. from `embedded-hal` 0.2) are wrapped using
+//! [`Error::untyped()`] with a classifier or as [`Unknown`].
+//!
+//! Every family `ErrorKind` has a stable numeric [`Code`] and maps to a POSIX
+//! `errno` using [`Error::errno()`].
+//! The `ehe-decode` host tool decodes codes and serialized errors.
+//! [`Report`] renders the full source chain of an error.
+//! [`DriverError`] is a ready-made error for drivers using a bus and pins.
+//!
+//! The optional `bus` feature adds [`DeviceError`] for `embedded-hal-bus`.
+//! The optional `retry` feature adds the [`Retry`] bus adapter.
+//! The optional `std` feature converts [`Error`] into `std::io::Error`.
+//! The optional `derive` feature adds [`macro@HalError`] for driver error enums.
+//! The optional `location` feature records the call site of conversions into [`Error`].
+//! The optional `defmt` feature implements `defmt::Format`.
+//! The optional `serde` feature implements `serde::Serialize` and `serde::Deserialize`.
+//! The optional `async` feature implements the `embedded-hal-async` and
+//! `embedded-io-async` traits for [`Wrapped`].
+
+#[cfg(feature = "location")]
+use core::panic::Location;
+#[cfg(feature = "std")]
+extern crate std;
 
 use core::{error, fmt};
 
+#[macro_use]
+mod table;
+mod family;
+mod kind;
+pub use kind::{Family, HalErrorKind, KindOf, NoAcknowledgeSource};
+mod class;
+pub use class::{Class, Classify, DefaultPolicy, Policy};
+mod code;
+pub use code::{Code, CodeEntry, CODES};
+mod errno;
+
+mod context;
+pub use context::{Context, ResultExt};
+mod report;
+pub use report::Report;
+mod chained;
+pub use chained::Chained;
+mod driver;
+pub use driver::DriverError;
+mod untyped;
+pub use untyped::{Unknown, UnknownError};
+
+#[cfg(feature = "std")]
+mod std_io;
+
+#[cfg(feature = "serial-nb")]
+mod serial_nb;
+#[cfg(feature = "serial-nb")]
+pub use serial_nb::{block_timeout, NbResultExt, SerialTimeout, SerialTimeoutKind};
+
+mod wrapped;
+pub use wrapped::Wrapped;
+
+#[cfg(feature = "retry")]
+mod retry;
+#[cfg(feature = "retry")]
+pub use retry::{Retried, Retry};
+
+#[cfg(feature = "serde")]
+mod shim;
+#[cfg(feature = "serde")]
+pub use shim::*;
+
+#[cfg(feature = "derive")]
+pub use embedded_hal_error_derive::HalError;
+#[cfg(all(test, feature = "derive", feature = "digital", feature = "spi"))]
+extern crate self as embedded_hal_error;
+
+/// Upstream family modules for [`macro@HalError`]
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod derive {
+    #[cfg(feature = "can")]
+    pub use embedded_can as can;
+    #[cfg(feature = "digital")]
+    pub use embedded_hal::digital;
+    #[cfg(feature = "i2c")]
+    pub use embedded_hal::i2c;
+    #[cfg(feature = "pwm")]
+    pub use embedded_hal::pwm;
+    #[cfg(feature = "spi")]
+    pub use embedded_hal::spi;
+    #[cfg(feature = "serial-nb")]
+    pub use embedded_hal_nb::serial;
+    #[cfg(feature = "io")]
+    pub use embedded_io as io;
+    #[cfg(feature = "nor-flash")]
+    pub use embedded_storage::nor_flash;
+}
+
+#[cfg(feature = "bus")]
+mod bus;
+#[cfg(feature = "bus")]
+pub use bus::DeviceError;
+
 /// Wrap a HAL `Error` and store its `ErrorKind` to provide [`core::error::Error`]
 ///
 /// Uses `E: Debug` for `Debug` and `Display` and the
 /// stored `ErrorKind` as [`core::error::Error::source()`].
+/// Use [`Chained`] to also expose an `E: core::error::Error` in the source chain.
+///
+/// Implements the family `Error` trait (e.g. `embedded_hal::i2c::Error`) returning
+/// the stored `ErrorKind`. Wrapped errors can thus be used wherever
+/// an error of that family is expected.
+///
+/// With the `location` feature the call site of the conversion
+/// (e.g. the `?`) is recorded and appended to the alternate `{:#}` `Display`.
+/// Function pointers can not carry their caller: converting using e.g.
+/// `.map_err(DigitalError::from)` or `.map_err(DriverError::pin)` records a
+/// location inside `core`. Convert using `?` or a `match` to record the call site.
 pub struct Error<E, K> {
     inner: E,
     kind: K,
+    #[cfg(feature = "location")]
+    location: &'static Location<'static>,
 }
 
 impl<E, K> Error<E, K> {
+    #[track_caller]
+    fn new(inner: E, kind: K) -> Self {
+        Self {
+            inner,
+            kind,
+            #[cfg(feature = "location")]
+            location: Location::caller(),
+        }
+    }
+
     /// Extract the inner `Error`
     pub fn into_inner(self) -> E {
         self.inner
     }
+
+    /// Map the inner `Error` keeping the `ErrorKind`
+    pub fn map<F>(self, op: impl FnOnce(E) -> F) -> Error<F, K> {
+        Error {
+            inner: op(self.inner),
+            kind: self.kind,
+            #[cfg(feature = "location")]
+            location: self.location,
+        }
+    }
+
+    /// The call site where the `Error` was created
+    #[cfg(feature = "location")]
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl<E, K: Copy + Into<HalErrorKind>> Error<E, K> {
+    /// The unified [`HalErrorKind`] of the stored `ErrorKind`
+    pub fn hal_kind(&self) -> HalErrorKind {
+        self.kind.into()
+    }
 }
 
 impl<E, K> core::ops::Deref for Error<E, K> {
@@ -33,7 +188,12 @@ impl<E, K> core::ops::Deref for Error<E, K> {
 
 impl<E: fmt::Debug, K> fmt::Display for Error<E, K> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.inner.fmt(f)
+        self.inner.fmt(f)?;
+        #[cfg(feature = "location")]
+        if f.alternate() {
+            write!(f, " at {}", self.location)?;
+        }
+        Ok(())
     }
 }
 
@@ -49,27 +209,86 @@ impl<E: fmt::Debug, K: error::Error + 'static> error::Error for Error<E, K> {
     }
 }
 
+impl<E, K> From<E> for Error<E, K>
+where
+    Self: KindOf<K, E>,
+{
+    #[track_caller]
+    fn from(inner: E) -> Self {
+        let kind = Self::kind_of(&inner);
+        Self::new(inner, kind)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<E: defmt::Format, K: Family> defmt::Format for Error<E, K> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        self.kind.format(&self.inner, f)
+    }
+}
+
 macro_rules! impl_from {
-    ($($mod:ident)::+) => {
-        impl<E: $($mod ::)+ Error> From<E> for Error<E, $($mod ::)+ ErrorKind> {
-            fn from(inner: E) -> Self {
-                let kind = inner.kind();
-                Self { inner, kind }
+    ($(
+        $feature:literal $alias:ident $shim:ident:
+            $($mod:ident)::+, $error:ident, $kind:ident, $family:literal = $code:literal {
+            $(
+                $($name:literal)? $variant:ident
+                $(($arg:ident: $argty:ident {
+                    $($subname:literal $sub:ident = $subcode:literal),+ $(,)?
+                }))?
+                $(= $variant_code:literal)? => $hal:expr
+            ),+ $(,)?
+        }
+    )+) => {$(
+        #[cfg(feature = $feature)]
+        #[doc = concat!("[`Error`] for `", stringify!($($mod)::+), "` HAL errors")]
+        pub type $alias<E> = Error<E, $($mod ::)+ $kind>;
+
+        #[cfg(feature = $feature)]
+        impl<E: $($mod ::)+ $error> KindOf<$($mod ::)+ $kind, E> for $alias<E> {
+            fn kind_of(error: &E) -> $($mod ::)+ $kind {
+                error.kind()
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl<E: $($mod ::)+ $error> $($mod ::)+ $error for $alias<E> {
+            fn kind(&self) -> $($mod ::)+ $kind {
+                self.kind
             }
         }
-    };
+
+        #[cfg(feature = $feature)]
+        impl Family for $($mod ::)+ $kind {
+            const NAME: &'static str = $family;
+
+            #[cfg(feature = "defmt")]
+            fn format<E: defmt::Format>(self, inner: &E, f: defmt::Formatter<'_>) {
+                #[allow(unreachable_patterns)]
+                let kind = match Code::variant(self) {
+                    $(
+                        $($variant_code => defmt::intern!($name),)?
+                        $($($subcode => defmt::intern!($subname),)+)?
+                    )+
+                    _ => defmt::intern!("Unknown"),
+                };
+                defmt::write!(
+                    f,
+                    "{=istr}: {=istr} <- {}",
+                    defmt::intern!($family),
+                    kind,
+                    inner
+                )
+            }
+        }
+    )+};
 }
 
-impl_from!(embedded_hal::digital);
-impl_from!(embedded_hal::i2c);
-impl_from!(embedded_hal::pwm);
-impl_from!(embedded_hal::spi);
-impl_from!(embedded_can);
-impl_from!(embedded_hal_nb::serial);
-impl_from!(embedded_io);
+families!(impl_from);
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "digital")]
     mod hal {
         use embedded_hal::digital;
 
@@ -95,6 +314,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "digital")]
     mod driver {
         use embedded_hal::digital;
 
@@ -116,6 +336,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "digital")]
     fn inspect() {
         use core::error::Error as _;
         use embedded_hal::digital::{Error as _, ErrorKind};
@@ -132,8 +353,347 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "digital")]
+    fn find() {
+        use crate::HalErrorKind;
+
+        let driver_err = driver::action(&mut hal::Pin).unwrap_err();
+        assert_eq!(HalErrorKind::find(&driver_err), Some(HalErrorKind::Other));
+        assert_eq!(
+            HalErrorKind::find(&HalErrorKind::Timeout),
+            Some(HalErrorKind::Timeout)
+        );
+        assert_eq!(HalErrorKind::find(&core::fmt::Error), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i2c", feature = "digital"))]
+    fn find_nack() {
+        use crate::{HalErrorKind, NoAcknowledgeSource, ResultExt};
+        use embedded_hal::i2c::{self, ErrorKind};
+
+        #[derive(Debug)]
+        struct Error;
+        impl i2c::Error for Error {
+            fn kind(&self) -> ErrorKind {
+                ErrorKind::NoAcknowledge(i2c::NoAcknowledgeSource::Address)
+            }
+        }
+
+        // Application level handler that does not know the driver types
+        fn is_nack(err: &(dyn core::error::Error + 'static)) -> bool {
+            matches!(
+                HalErrorKind::find(err),
+                Some(HalErrorKind::NoAcknowledge(_))
+            )
+        }
+
+        let err = Err::<(), _>(Error).context("probing").unwrap_err();
+        assert!(is_nack(&err));
+        assert_eq!(
+            HalErrorKind::find(&err),
+            Some(HalErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+        );
+        assert!(!is_nack(&driver::action(&mut hal::Pin).unwrap_err()));
+    }
+
+    #[test]
+    #[cfg(feature = "digital")]
     #[ignore]
     fn with_anyhow() -> anyhow::Result<()> {
         Ok(driver::action(&mut hal::Pin)?)
     }
+
+    #[test]
+    #[cfg(feature = "digital")]
+    fn report() {
+        extern crate std;
+        use crate::Report;
+        use std::{format, string::String};
+
+        let kind = "A different error occurred. The original error may contain more information";
+        let driver_err = driver::action(&mut hal::Pin).unwrap_err();
+        assert_eq!(
+            format!("{}", Report::new(&driver_err)),
+            format!("Hal: Error: {kind}")
+        );
+        assert_eq!(
+            format!("{}", Report::new(&driver_err).with_multiline(true)),
+            format!("Hal\n\nCaused by:\n    0: Error\n    1: {kind}")
+        );
+        assert_eq!(
+            format!("{}", Report::new(&driver_err).with_depth(1)),
+            "Hal: Error: ..."
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                Report::new(&driver_err).with_multiline(true).with_depth(0)
+            ),
+            "Hal\n\nCaused by:\n    ..."
+        );
+        let mut s = String::new();
+        Report::new(crate::DigitalError::from(hal::Error))
+            .write(&mut s)
+            .unwrap();
+        assert_eq!(s, format!("Error: {kind}"));
+    }
+
+    #[cfg(all(feature = "derive", feature = "digital", feature = "spi"))]
+    mod derived {
+        use embedded_hal::{digital, spi};
+
+        #[derive(Debug)]
+        pub struct SpiError;
+        impl spi::Error for SpiError {
+            fn kind(&self) -> spi::ErrorKind {
+                spi::ErrorKind::Overrun
+            }
+        }
+
+        #[derive(Debug, crate::HalError)]
+        pub enum Error<S: spi::Error, P: digital::Error> {
+            #[hal(spi, from)]
+            Spi(crate::SpiError<S>),
+            #[hal(digital)]
+            Irq(crate::Error<P, digital::ErrorKind>),
+            #[hal(display = "invalid chip ID {_0:#x}")]
+            ChipId(u8),
+            Timeout,
+        }
+
+        fn read() -> Result<u8, SpiError> {
+            Err(SpiError)
+        }
+
+        pub fn transfer() -> Result<u8, Error<SpiError, super::hal::Error>> {
+            Ok(read()?)
+        }
+
+        pub fn reset<P: digital::OutputPin>(pin: &mut P) -> Result<(), Error<SpiError, P::Error>> {
+            Ok(pin.set_high().map_err(crate::DigitalError::from)?)
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "derive", feature = "digital", feature = "spi"))]
+    fn derive() {
+        extern crate std;
+        use crate::{HalErrorKind, Report};
+        use core::error::Error as _;
+        use derived::Error;
+        use std::{format, string::ToString};
+
+        let err = derived::transfer().unwrap_err();
+        assert_eq!(err.to_string(), "spi");
+        let _: &crate::SpiError<derived::SpiError> = err.source().unwrap().downcast_ref().unwrap();
+        assert_eq!(HalErrorKind::find(&err), Some(HalErrorKind::Overrun));
+
+        let err = derived::reset(&mut hal::Pin).unwrap_err();
+        assert!(matches!(err, Error::Irq(_)));
+        assert!(format!("{}", Report::new(&err)).starts_with("digital: Error: "));
+
+        let err: Error<derived::SpiError, hal::Error> = Error::ChipId(0x42);
+        assert_eq!(err.to_string(), "invalid chip ID 0x42");
+        assert!(err.source().is_none());
+        let err: Error<derived::SpiError, hal::Error> = Error::Timeout;
+        assert_eq!(err.to_string(), "Timeout");
+    }
+
+    #[cfg(feature = "nor-flash")]
+    mod flash {
+        use embedded_storage::nor_flash::{self, NorFlashErrorKind};
+
+        #[derive(Debug)]
+        pub struct Error;
+        impl nor_flash::NorFlashError for Error {
+            fn kind(&self) -> NorFlashErrorKind {
+                NorFlashErrorKind::OutOfBounds
+            }
+        }
+
+        pub fn erase() -> Result<(), Error> {
+            Err(Error)
+        }
+
+        pub fn action() -> Result<(), crate::NorFlashError<Error>> {
+            Ok(erase()?)
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "nor-flash")]
+    fn inspect_nor_flash() {
+        use core::error::Error as _;
+        use embedded_storage::nor_flash::{NorFlashError as _, NorFlashErrorKind};
+
+        let err = flash::action().unwrap_err();
+        let flash_err: &flash::Error = &err; // Deref
+        assert!(matches!(flash_err.kind(), NorFlashErrorKind::OutOfBounds));
+        let kind_dyn = err.source().unwrap();
+        let kind: &NorFlashErrorKind = kind_dyn.downcast_ref().unwrap();
+        assert!(matches!(kind, NorFlashErrorKind::OutOfBounds));
+        assert!(kind_dyn.source().is_none());
+    }
+
+    #[test]
+    #[cfg(all(feature = "location", feature = "nor-flash"))]
+    fn location() {
+        extern crate std;
+
+        let res: Result<(), crate::NorFlashError<_>> = (|| Ok(flash::erase()?))();
+        let line = line!() - 1;
+        let err = res.unwrap_err();
+        let location = err.location();
+        assert_eq!(location.file(), file!());
+        assert_eq!(location.line(), line);
+        assert_eq!(std::format!("{err}"), "Error");
+        assert_eq!(
+            std::format!("{err:#}"),
+            std::format!("Error at {}:{}:{}", file!(), line, location.column())
+        );
+        // Mapping keeps the location
+        assert_eq!(err.map(|_| ()).location(), location);
+    }
+
+    #[test]
+    fn family_traits() {
+        macro_rules! round_trip {
+            ($feature:literal, $alias:ident, $($mod:ident)::+, $error:ident, $kind:ident, $value:expr) => {
+                #[cfg(feature = $feature)]
+                {
+                    use $($mod::)+{$error, $kind};
+                    fn kind<E: $error>(err: E) -> $kind {
+                        err.kind()
+                    }
+                    let err: crate::$alias<_> = $value.into();
+                    assert_eq!(kind(err), $value);
+                    // Wrapping again preserves the kind
+                    let err: crate::$alias<crate::$alias<_>> = crate::$alias::from($value).into();
+                    assert_eq!(kind(err), $value);
+                }
+            };
+        }
+        round_trip!(
+            "digital",
+            DigitalError,
+            embedded_hal::digital,
+            Error,
+            ErrorKind,
+            ErrorKind::Other
+        );
+        round_trip!(
+            "i2c",
+            I2cError,
+            embedded_hal::i2c,
+            Error,
+            ErrorKind,
+            ErrorKind::ArbitrationLoss
+        );
+        round_trip!(
+            "pwm",
+            PwmError,
+            embedded_hal::pwm,
+            Error,
+            ErrorKind,
+            ErrorKind::Other
+        );
+        round_trip!(
+            "spi",
+            SpiError,
+            embedded_hal::spi,
+            Error,
+            ErrorKind,
+            ErrorKind::ModeFault
+        );
+        round_trip!(
+            "can",
+            CanError,
+            embedded_can,
+            Error,
+            ErrorKind,
+            ErrorKind::Stuff
+        );
+        round_trip!(
+            "serial-nb",
+            SerialError,
+            embedded_hal_nb::serial,
+            Error,
+            ErrorKind,
+            ErrorKind::Noise
+        );
+        round_trip!(
+            "io",
+            IoError,
+            embedded_io,
+            Error,
+            ErrorKind,
+            ErrorKind::BrokenPipe
+        );
+        round_trip!(
+            "nor-flash",
+            NorFlashError,
+            embedded_storage::nor_flash,
+            NorFlashError,
+            NorFlashErrorKind,
+            NorFlashErrorKind::NotAligned
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "defmt", feature = "i2c"))]
+    fn defmt() {
+        extern crate std;
+        use embedded_hal::i2c::{self, ErrorKind, NoAcknowledgeSource};
+        use std::vec::Vec;
+
+        #[derive(Debug, defmt::Format)]
+        struct Error(u8);
+        impl i2c::Error for Error {
+            fn kind(&self) -> ErrorKind {
+                ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)
+            }
+        }
+
+        // Interning is mocked by a thread local counter bumped on every use
+        let index = defmt::export::fetch_string_index();
+        let err = crate::I2cError::from(Error(0x55));
+        defmt::export::istr(&<crate::I2cError<Error> as defmt::Format>::_format_tag());
+        defmt::Format::_format_data(&err);
+
+        let mut expect = Vec::new();
+        for i in [
+            index,     // format sequence tag
+            index + 3, // "{=istr}: {=istr} <- {}"
+            index + 2, // "i2c"
+            index + 1, // "NoAcknowledge(Data)"
+            index + 4, // inner `Error` tag
+        ] {
+            expect.extend(i.to_le_bytes());
+        }
+        expect.push(0x55); // inner `Error` data
+        expect.extend(0u16.to_le_bytes()); // format sequence end
+        assert_eq!(defmt::export::fetch_bytes(), expect);
+    }
+
+    #[test]
+    fn aliases() {
+        #[cfg(feature = "digital")]
+        let _: crate::DigitalError<_> = embedded_hal::digital::ErrorKind::Other.into();
+        #[cfg(feature = "i2c")]
+        let _: crate::I2cError<_> = embedded_hal::i2c::ErrorKind::Bus.into();
+        #[cfg(feature = "pwm")]
+        let _: crate::PwmError<_> = embedded_hal::pwm::ErrorKind::Other.into();
+        #[cfg(feature = "spi")]
+        let _: crate::SpiError<_> = embedded_hal::spi::ErrorKind::ModeFault.into();
+        #[cfg(feature = "can")]
+        let _: crate::CanError<_> = embedded_can::ErrorKind::Crc.into();
+        #[cfg(feature = "serial-nb")]
+        let _: crate::SerialError<_> = embedded_hal_nb::serial::ErrorKind::Parity.into();
+        #[cfg(feature = "io")]
+        let _: crate::IoError<_> = embedded_io::ErrorKind::TimedOut.into();
+        #[cfg(feature = "nor-flash")]
+        let _: crate::NorFlashError<_> =
+            embedded_storage::nor_flash::NorFlashErrorKind::NotAligned.into();
+    }
 }
@@ -0,0 +1,100 @@
+//! Rendering of error source chains
+
+use core::{error, fmt};
+
+/// Render an error and its [`core::error::Error::source()`] chain
+///
+/// Single line (the default):
+///
+/// ```text
+/// driver error: Error: bus error
+/// ```
+///
+/// Multi line:
+///
+/// ```text
+/// driver error
+///
+/// Caused by:
+///     0: Error
+///     1: bus error
+/// ```
+///
+/// Implements `Display`. Render into any [`core::fmt::Write`] using `write!()`
+/// or [`Report::write()`].
+pub struct Report<E> {
+    error: E,
+    multiline: bool,
+    depth: usize,
+}
+
+impl<E> Report<E> {
+    /// Create a single line report with unlimited depth
+    pub fn new(error: E) -> Self {
+        Self {
+            error,
+            multiline: false,
+            depth: usize::MAX,
+        }
+    }
+
+    /// Render the sources on separate lines
+    pub fn with_multiline(mut self, multiline: bool) -> Self {
+        self.multiline = multiline;
+        self
+    }
+
+    /// Limit the number of sources rendered
+    ///
+    /// Omitted sources are indicated by `...`.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Extract the inner error
+    pub fn into_inner(self) -> E {
+        self.error
+    }
+}
+
+impl<E: error::Error> Report<E> {
+    /// Render into a [`core::fmt::Write`]
+    pub fn write<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        write!(w, "{self}")
+    }
+}
+
+impl<E: error::Error> fmt::Display for Report<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        let mut source = self.error.source();
+        if self.multiline && source.is_some() {
+            write!(f, "\n\nCaused by:")?;
+        }
+        let mut i = 0;
+        while let Some(error) = source {
+            if i >= self.depth {
+                return if self.multiline {
+                    write!(f, "\n    ...")
+                } else {
+                    write!(f, ": ...")
+                };
+            }
+            if self.multiline {
+                write!(f, "\n    {i}: {error}")?;
+            } else {
+                write!(f, ": {error}")?;
+            }
+            source = error.source();
+            i += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<E: error::Error> fmt::Debug for Report<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
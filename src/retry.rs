@@ -0,0 +1,520 @@
+//! Retrying bus adapters
+
+use core::{error, fmt, marker::PhantomData};
+use embedded_hal::delay::DelayNs;
+
+use crate::{Class, DefaultPolicy, Error, HalErrorKind, Policy};
+
+/// Wrap a bus and retry operations failing with a transient error
+///
+/// Operations are attempted at most `attempts` times with a delay of `backoff_us`
+/// between attempts. Whether an error is transient is determined by the
+/// [`Policy`] `P`.
+///
+/// Implements `embedded_hal::i2c::I2c`, `embedded_hal::spi::SpiDevice`,
+/// `embedded_io::Read` and `embedded_io::Write` if the inner bus does.
+/// The error type is [`Retried`]. With the `location` feature the caller
+/// of a method is recorded as the location of its error.
+///
+/// A retry replays the entire operation, including the side effects of the
+/// failed attempt: a SPI transaction reading from a device FIFO consumes
+/// that data again, and a partially completed write is sent again.
+/// Only wrap buses and devices whose operations can be repeated safely.
+pub struct Retry<T, D, P = DefaultPolicy> {
+    inner: T,
+    delay: D,
+    attempts: u32,
+    backoff_us: u32,
+    _policy: PhantomData<P>,
+}
+
+impl<T, D> Retry<T, D> {
+    /// Wrap a bus using the [`DefaultPolicy`]
+    ///
+    /// Defaults to three attempts with 100 µs backoff.
+    pub fn new(inner: T, delay: D) -> Self {
+        Self {
+            inner,
+            delay,
+            attempts: 3,
+            backoff_us: 100,
+            _policy: PhantomData,
+        }
+    }
+}
+
+impl<T, D, P> Retry<T, D, P> {
+    /// Set the maximum number of attempts (at least one)
+    pub fn with_attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Set the delay between attempts
+    pub fn with_backoff_us(mut self, backoff_us: u32) -> Self {
+        self.backoff_us = backoff_us;
+        self
+    }
+
+    /// Use a different classification policy
+    pub fn with_policy<Q: Policy>(self) -> Retry<T, D, Q> {
+        Retry {
+            inner: self.inner,
+            delay: self.delay,
+            attempts: self.attempts,
+            backoff_us: self.backoff_us,
+            _policy: PhantomData,
+        }
+    }
+
+    /// Extract the inner bus and delay
+    pub fn into_inner(self) -> (T, D) {
+        (self.inner, self.delay)
+    }
+}
+
+impl<T, D: DelayNs, P: Policy> Retry<T, D, P> {
+    #[allow(dead_code)] // without any of the bus families
+    #[track_caller]
+    fn retry<R, E, K>(
+        &mut self,
+        mut op: impl FnMut(&mut T) -> Result<R, E>,
+    ) -> Result<R, Retried<E, K>>
+    where
+        Error<E, K>: From<E>,
+        K: Copy + Into<HalErrorKind>,
+    {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match op(&mut self.inner) {
+                Ok(ret) => return Ok(ret),
+                Err(err) => {
+                    let error = Error::from(err);
+                    if attempts >= self.attempts || error.class_with::<P>() != Class::Transient {
+                        return Err(Retried { error, attempts });
+                    }
+                    self.delay.delay_us(self.backoff_us);
+                }
+            }
+        }
+    }
+}
+
+/// [`Error`] of the final attempt of a [`Retry`] operation
+pub struct Retried<E, K> {
+    error: Error<E, K>,
+    attempts: u32,
+}
+
+impl<E, K> Retried<E, K> {
+    /// Number of attempts made
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Extract the inner [`Error`]
+    pub fn into_inner(self) -> Error<E, K> {
+        self.error
+    }
+}
+
+impl<E, K> core::ops::Deref for Retried<E, K> {
+    type Target = Error<E, K>;
+    fn deref(&self) -> &Self::Target {
+        &self.error
+    }
+}
+
+impl<E: fmt::Debug, K> fmt::Display for Retried<E, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (after {} attempts)", self.error, self.attempts)
+    }
+}
+
+impl<E: fmt::Debug, K> fmt::Debug for Retried<E, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Retried")
+            .field("error", &self.error)
+            .field("attempts", &self.attempts)
+            .finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<E, K> defmt::Format for Retried<E, K>
+where
+    Error<E, K>: defmt::Format,
+{
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{} (after {=u32} attempts)", self.error, self.attempts)
+    }
+}
+
+impl<E: fmt::Debug + 'static, K: error::Error + 'static> error::Error for Retried<E, K> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(feature = "i2c")]
+mod i2c {
+    use embedded_hal::{delay::DelayNs, i2c};
+
+    use super::{Retried, Retry};
+    use crate::Policy;
+
+    impl<E: core::fmt::Debug> i2c::Error for Retried<E, i2c::ErrorKind> {
+        fn kind(&self) -> i2c::ErrorKind {
+            self.error.kind
+        }
+    }
+
+    impl<T: i2c::ErrorType, D, P> i2c::ErrorType for Retry<T, D, P> {
+        type Error = Retried<T::Error, i2c::ErrorKind>;
+    }
+
+    impl<A: i2c::AddressMode + Copy, T: i2c::I2c<A>, D: DelayNs, P: Policy> i2c::I2c<A>
+        for Retry<T, D, P>
+    {
+        #[track_caller]
+        fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+            self.retry(|i| i.read(address, read))
+        }
+
+        #[track_caller]
+        fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+            self.retry(|i| i.write(address, write))
+        }
+
+        #[track_caller]
+        fn write_read(
+            &mut self,
+            address: A,
+            write: &[u8],
+            read: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            self.retry(|i| i.write_read(address, write, read))
+        }
+
+        #[track_caller]
+        fn transaction(
+            &mut self,
+            address: A,
+            operations: &mut [i2c::Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.retry(|i| i.transaction(address, operations))
+        }
+    }
+}
+
+#[cfg(feature = "spi")]
+mod spi {
+    use embedded_hal::{delay::DelayNs, spi};
+
+    use super::{Retried, Retry};
+    use crate::Policy;
+
+    impl<E: core::fmt::Debug> spi::Error for Retried<E, spi::ErrorKind> {
+        fn kind(&self) -> spi::ErrorKind {
+            self.error.kind
+        }
+    }
+
+    impl<T: spi::ErrorType, D, P> spi::ErrorType for Retry<T, D, P> {
+        type Error = Retried<T::Error, spi::ErrorKind>;
+    }
+
+    impl<W: Copy + 'static, T: spi::SpiDevice<W>, D: DelayNs, P: Policy> spi::SpiDevice<W>
+        for Retry<T, D, P>
+    {
+        #[track_caller]
+        fn transaction(
+            &mut self,
+            operations: &mut [spi::Operation<'_, W>],
+        ) -> Result<(), Self::Error> {
+            self.retry(|i| i.transaction(operations))
+        }
+
+        #[track_caller]
+        fn read(&mut self, buf: &mut [W]) -> Result<(), Self::Error> {
+            self.retry(|i| i.read(buf))
+        }
+
+        #[track_caller]
+        fn write(&mut self, buf: &[W]) -> Result<(), Self::Error> {
+            self.retry(|i| i.write(buf))
+        }
+
+        #[track_caller]
+        fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+            self.retry(|i| i.transfer(read, write))
+        }
+
+        #[track_caller]
+        fn transfer_in_place(&mut self, buf: &mut [W]) -> Result<(), Self::Error> {
+            self.retry(|i| i.transfer_in_place(buf))
+        }
+    }
+}
+
+#[cfg(feature = "io")]
+mod io {
+    use embedded_hal::delay::DelayNs;
+    use embedded_io as io;
+
+    use super::{Retried, Retry};
+    use crate::Policy;
+
+    impl<E: core::fmt::Debug> io::Error for Retried<E, io::ErrorKind> {
+        fn kind(&self) -> io::ErrorKind {
+            self.error.kind
+        }
+    }
+
+    impl<T: io::ErrorType, D, P> io::ErrorType for Retry<T, D, P> {
+        type Error = Retried<T::Error, io::ErrorKind>;
+    }
+
+    impl<T: io::Read, D: DelayNs, P: Policy> io::Read for Retry<T, D, P> {
+        #[track_caller]
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            self.retry(|i| i.read(buf))
+        }
+    }
+
+    impl<T: io::Write, D: DelayNs, P: Policy> io::Write for Retry<T, D, P> {
+        #[track_caller]
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.retry(|i| i.write(buf))
+        }
+
+        #[track_caller]
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.retry(|i| i.flush())
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "i2c", feature = "spi", feature = "io")))]
+mod tests {
+    use embedded_hal::delay::DelayNs;
+
+    use super::Retry;
+
+    /// Bus failing the first `fail` operations with `kind`
+    struct Mock<K> {
+        fail: u32,
+        kind: K,
+        calls: u32,
+    }
+
+    impl<K: Copy> Mock<K> {
+        fn attempt(&mut self) -> Result<(), K> {
+            self.calls += 1;
+            if self.calls <= self.fail {
+                Err(self.kind)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Delay(u32);
+
+    impl DelayNs for Delay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0 += ns;
+        }
+    }
+
+    fn mock<K>(fail: u32, kind: K) -> Retry<Mock<K>, Delay> {
+        Retry::new(
+            Mock {
+                fail,
+                kind,
+                calls: 0,
+            },
+            Delay::default(),
+        )
+    }
+
+    #[cfg(feature = "i2c")]
+    mod i2c {
+        use embedded_hal::i2c;
+
+        use super::{mock, Mock};
+
+        impl i2c::ErrorType for Mock<i2c::ErrorKind> {
+            type Error = i2c::ErrorKind;
+        }
+
+        impl i2c::I2c for Mock<i2c::ErrorKind> {
+            fn transaction(
+                &mut self,
+                _address: u8,
+                _operations: &mut [i2c::Operation<'_>],
+            ) -> Result<(), Self::Error> {
+                self.attempt()
+            }
+        }
+
+        #[test]
+        fn transient() {
+            use i2c::I2c;
+
+            let mut bus = mock(2, i2c::ErrorKind::ArbitrationLoss);
+            bus.write(0x10, &[1]).unwrap();
+            let (bus, delay) = bus.into_inner();
+            assert_eq!(bus.calls, 3);
+            assert_eq!(delay.0, 2 * 100_000);
+        }
+
+        #[test]
+        fn exhausted() {
+            use i2c::{Error as _, I2c};
+
+            let mut bus = mock(5, i2c::ErrorKind::Bus).with_attempts(4);
+            let err = bus.write(0x10, &[1]).unwrap_err();
+            assert_eq!(err.attempts(), 4);
+            #[cfg(feature = "location")]
+            assert_eq!(
+                (err.location().file(), err.location().line()),
+                (file!(), line!() - 5)
+            );
+            assert!(matches!(err.kind(), i2c::ErrorKind::Bus));
+            assert!(err.is_transient());
+            assert_eq!(bus.into_inner().0.calls, 4);
+        }
+
+        #[test]
+        fn fatal() {
+            use core::error::Error as _;
+            use i2c::I2c;
+
+            let mut bus = mock(1, i2c::ErrorKind::Other);
+            let err = bus.read(0x10, &mut [0]).unwrap_err();
+            assert_eq!(err.attempts(), 1);
+            let kind: &i2c::ErrorKind = err
+                .source()
+                .unwrap()
+                .source()
+                .unwrap()
+                .downcast_ref()
+                .unwrap();
+            assert!(matches!(kind, i2c::ErrorKind::Other));
+        }
+    }
+
+    #[cfg(feature = "spi")]
+    mod spi {
+        use embedded_hal::spi;
+
+        use super::{mock, Mock};
+
+        impl spi::ErrorType for Mock<spi::ErrorKind> {
+            type Error = spi::ErrorKind;
+        }
+
+        impl spi::SpiDevice for Mock<spi::ErrorKind> {
+            fn transaction(
+                &mut self,
+                operations: &mut [spi::Operation<'_, u8>],
+            ) -> Result<(), Self::Error> {
+                self.attempt()?;
+                for op in operations.iter_mut() {
+                    if let spi::Operation::Read(buf) = op {
+                        buf.fill(self.calls as u8);
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn transient() {
+            use spi::SpiDevice;
+
+            let mut dev = mock(1, spi::ErrorKind::Overrun).with_backoff_us(10);
+            let mut buf = [0; 2];
+            dev.read(&mut buf).unwrap();
+            // the failed attempt was replayed
+            assert_eq!(buf, [2; 2]);
+            let (dev, delay) = dev.into_inner();
+            assert_eq!(dev.calls, 2);
+            assert_eq!(delay.0, 10_000);
+        }
+
+        #[test]
+        fn fatal() {
+            use spi::{Error as _, SpiDevice};
+
+            let mut dev = mock(3, spi::ErrorKind::ModeFault);
+            let err = dev.write(&[1, 2]).unwrap_err();
+            assert_eq!(err.attempts(), 1);
+            assert!(matches!(err.kind(), spi::ErrorKind::ModeFault));
+            assert!(err.is_fatal());
+            assert_eq!(dev.into_inner().0.calls, 1);
+        }
+    }
+
+    #[cfg(feature = "io")]
+    mod io {
+        use embedded_io as io;
+
+        use super::{mock, Mock};
+
+        impl io::ErrorType for Mock<io::ErrorKind> {
+            type Error = io::ErrorKind;
+        }
+
+        impl io::Read for Mock<io::ErrorKind> {
+            fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+                self.attempt()?;
+                buf[0] = self.calls as u8;
+                Ok(1)
+            }
+        }
+
+        impl io::Write for Mock<io::ErrorKind> {
+            fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+                self.attempt()?;
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> Result<(), Self::Error> {
+                self.attempt()
+            }
+        }
+
+        #[test]
+        fn read() {
+            use io::Read;
+
+            let mut port = mock(2, io::ErrorKind::Interrupted);
+            let mut buf = [0; 4];
+            assert_eq!(port.read(&mut buf).unwrap(), 1);
+            assert_eq!(buf[0], 3);
+            assert_eq!(port.into_inner().0.calls, 3);
+        }
+
+        #[test]
+        fn write() {
+            use io::{Error as _, Write};
+
+            let mut port = mock(1, io::ErrorKind::TimedOut);
+            assert_eq!(port.write(&[1, 2, 3]).unwrap(), 3);
+
+            let mut port = mock(5, io::ErrorKind::TimedOut).with_attempts(2);
+            let err = port.flush().unwrap_err();
+            assert_eq!(err.attempts(), 2);
+            assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+            let mut port = mock(5, io::ErrorKind::BrokenPipe);
+            let err = port.write(&[1]).unwrap_err();
+            assert_eq!(err.attempts(), 1);
+            assert!(err.is_fatal());
+        }
+    }
+}
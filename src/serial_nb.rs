@@ -0,0 +1,246 @@
+//! `nb` support for `embedded-hal-nb` serial
+
+use core::{error, fmt};
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal_nb::{nb, serial};
+
+use crate::{Code, Error, Family, HalErrorKind, KindOf, SerialError};
+
+/// Handle `WouldBlock` in the `nb::Result` of a serial operation
+pub trait NbResultExt<T, E> {
+    /// Split off `WouldBlock` as `Ok(None)` and wrap other errors in [`SerialError`]
+    fn ready(self) -> Result<Option<T>, SerialError<E>>;
+
+    /// Wrap other errors in [`SerialError`], keeping `WouldBlock`
+    fn wrap(self) -> nb::Result<T, SerialError<E>>;
+}
+
+impl<T, E: serial::Error> NbResultExt<T, E> for nb::Result<T, E> {
+    #[track_caller]
+    fn ready(self) -> Result<Option<T>, SerialError<E>> {
+        match self {
+            Ok(value) => Ok(Some(value)),
+            Err(nb::Error::WouldBlock) => Ok(None),
+            Err(nb::Error::Other(err)) => Err(err.into()),
+        }
+    }
+
+    #[track_caller]
+    fn wrap(self) -> nb::Result<T, SerialError<E>> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(nb::Error::WouldBlock) => Err(nb::Error::WouldBlock),
+            Err(nb::Error::Other(err)) => Err(nb::Error::Other(err.into())),
+        }
+    }
+}
+
+/// Serial error or timeout of [`block_timeout()`]
+///
+/// The serial `ErrorKind` of a timeout is `Other`.
+/// Wrapped in an [`Error`] its kind is a [`SerialTimeoutKind`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SerialTimeout<E> {
+    /// The operation did not complete in time
+    Elapsed,
+    /// Serial error
+    Serial(E),
+}
+
+impl<E: serial::Error> serial::Error for SerialTimeout<E> {
+    fn kind(&self) -> serial::ErrorKind {
+        match self {
+            Self::Elapsed => serial::ErrorKind::Other,
+            Self::Serial(err) => err.kind(),
+        }
+    }
+}
+
+/// `ErrorKind` of a [`SerialTimeout`]
+///
+/// Converts [`SerialTimeoutKind::Elapsed`] to [`HalErrorKind::Timeout`].
+/// The [`Family`] and [`Code`] are those of serial, with `Elapsed` encoded as `Other`.
+/// There is no `SerdeKind`: the serial shim can not represent `Elapsed`.
+/// Serialize the [`Error::code()`] or map the error to a [`SerialError`] instead.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SerialTimeoutKind {
+    /// The operation did not complete in time
+    Elapsed,
+    /// Serial error
+    Serial(serial::ErrorKind),
+}
+
+impl From<SerialTimeoutKind> for serial::ErrorKind {
+    fn from(value: SerialTimeoutKind) -> Self {
+        match value {
+            SerialTimeoutKind::Elapsed => Self::Other,
+            SerialTimeoutKind::Serial(kind) => kind,
+        }
+    }
+}
+
+impl From<SerialTimeoutKind> for HalErrorKind {
+    fn from(value: SerialTimeoutKind) -> Self {
+        match value {
+            SerialTimeoutKind::Elapsed => Self::Timeout,
+            SerialTimeoutKind::Serial(kind) => kind.into(),
+        }
+    }
+}
+
+impl fmt::Display for SerialTimeoutKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Elapsed => f.write_str("Elapsed"),
+            Self::Serial(kind) => kind.fmt(f),
+        }
+    }
+}
+
+impl error::Error for SerialTimeoutKind {}
+
+impl Family for SerialTimeoutKind {
+    const NAME: &'static str = serial::ErrorKind::NAME;
+}
+
+impl Code for SerialTimeoutKind {
+    const FAMILY: u8 = serial::ErrorKind::FAMILY;
+
+    fn variant(self) -> u8 {
+        serial::ErrorKind::from(self).variant()
+    }
+}
+
+impl<E: serial::Error> KindOf<SerialTimeoutKind, SerialTimeout<E>>
+    for Error<SerialTimeout<E>, SerialTimeoutKind>
+{
+    fn kind_of(error: &SerialTimeout<E>) -> SerialTimeoutKind {
+        match error {
+            SerialTimeout::Elapsed => SerialTimeoutKind::Elapsed,
+            SerialTimeout::Serial(err) => SerialTimeoutKind::Serial(err.kind()),
+        }
+    }
+}
+
+impl<E: fmt::Debug> serial::Error for Error<E, SerialTimeoutKind> {
+    fn kind(&self) -> serial::ErrorKind {
+        self.kind.into()
+    }
+}
+
+/// Block on a non-blocking serial operation with a timeout
+///
+/// Retries `op` every `interval_us` microseconds while it returns `WouldBlock`.
+/// An `interval_us` of zero retries every microsecond.
+/// Fails with [`SerialTimeout::Elapsed`] after `timeout_us` microseconds of delay.
+#[track_caller]
+pub fn block_timeout<T, E: serial::Error>(
+    delay: &mut impl DelayNs,
+    interval_us: u32,
+    timeout_us: u32,
+    mut op: impl FnMut() -> nb::Result<T, E>,
+) -> Result<T, Error<SerialTimeout<E>, SerialTimeoutKind>> {
+    let interval_us = interval_us.max(1);
+    let mut elapsed: u32 = 0;
+    loop {
+        match op() {
+            Ok(value) => return Ok(value),
+            Err(nb::Error::Other(err)) => return Err(SerialTimeout::Serial(err).into()),
+            Err(nb::Error::WouldBlock) if elapsed >= timeout_us => {
+                return Err(SerialTimeout::Elapsed.into())
+            }
+            Err(nb::Error::WouldBlock) => {
+                delay.delay_us(interval_us);
+                elapsed = elapsed.saturating_add(interval_us);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::delay::DelayNs;
+    use embedded_hal_nb::{nb, serial};
+
+    use super::{block_timeout, NbResultExt, SerialTimeout};
+    use crate::HalErrorKind;
+
+    #[derive(Debug, PartialEq)]
+    struct Error;
+    impl serial::Error for Error {
+        fn kind(&self) -> serial::ErrorKind {
+            serial::ErrorKind::Parity
+        }
+    }
+
+    struct Delay(u32);
+    impl DelayNs for Delay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0 = self.0.saturating_add(ns);
+        }
+    }
+
+    /// Read that would block `n` times
+    fn read(n: &mut u32) -> nb::Result<u8, Error> {
+        if *n == 0 {
+            Ok(0x55)
+        } else {
+            *n -= 1;
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    #[test]
+    fn ready() {
+        assert_eq!(read(&mut 1).ready().unwrap(), None);
+        assert_eq!(read(&mut 0).ready().unwrap(), Some(0x55));
+        let err = Err::<(), _>(nb::Error::Other(Error)).ready().unwrap_err();
+        assert_eq!(err.hal_kind(), HalErrorKind::Parity);
+        assert!(matches!(read(&mut 1).wrap(), Err(nb::Error::WouldBlock)));
+        let err = Err::<(), _>(nb::Error::Other(Error)).wrap();
+        assert!(matches!(err, Err(nb::Error::Other(err)) if *err == Error));
+    }
+
+    #[test]
+    fn block() {
+        let mut delay = Delay(0);
+        let mut n = 3;
+        assert_eq!(
+            block_timeout(&mut delay, 2, 10, || read(&mut n)).unwrap(),
+            0x55
+        );
+        assert_eq!(delay.0, 6_000);
+
+        let mut n = 20;
+        let err = block_timeout(&mut delay, 2, 10, || read(&mut n)).unwrap_err();
+        assert_eq!(*err, SerialTimeout::Elapsed);
+        assert_eq!(n, 14);
+        assert_eq!(err.hal_kind(), HalErrorKind::Timeout);
+        assert_eq!(HalErrorKind::find(&err), Some(HalErrorKind::Timeout));
+        assert!(err.is_transient());
+        assert_eq!(serial::Error::kind(&err), serial::ErrorKind::Other);
+        assert_eq!(err.code(), 0x0600);
+
+        let err =
+            block_timeout(&mut delay, 2, 10, || Err::<(), _>(nb::Error::Other(Error))).unwrap_err();
+        assert_eq!(*err, SerialTimeout::Serial(Error));
+        assert_eq!(err.hal_kind(), HalErrorKind::Parity);
+        assert_eq!(err.code(), 0x0603);
+    }
+
+    #[test]
+    fn block_interval() {
+        let mut delay = Delay(0);
+        let mut n = 20;
+        let err = block_timeout(&mut delay, 0, 3, || read(&mut n)).unwrap_err();
+        assert_eq!(*err, SerialTimeout::Elapsed);
+        assert_eq!((delay.0, n), (3_000, 16));
+
+        let mut n = 20;
+        let err = block_timeout(&mut delay, 1 << 31, u32::MAX, || read(&mut n)).unwrap_err();
+        assert_eq!(*err, SerialTimeout::Elapsed);
+        assert_eq!(n, 17);
+    }
+}
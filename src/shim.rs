@@ -0,0 +1,341 @@
+//! `serde` support
+//!
+//! The upstream `ErrorKind`s are not serializable. Each is mirrored by a shim
+//! enum with the same variants that is used in its place.
+//! The shims are re-exported at the crate root, e.g. `I2cErrorKind`.
+
+use core::{fmt, marker::PhantomData};
+use serde::{
+    de::{self, DeserializeSeed},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{Code, Error, Family, HalErrorKind, Unknown};
+
+/// `ErrorKind` with a serializable shim
+///
+/// Implemented for the `ErrorKind` of every supported family.
+pub trait SerdeKind: Family {
+    /// Serializable mirror of the `ErrorKind`
+    type Shim: Serialize + for<'de> Deserialize<'de> + From<Self> + Into<Self>;
+}
+
+/// Serialized as a struct `{family, kind, detail, inner}`
+///
+/// `family` is the [`Family::NAME`], `kind` the unified [`HalErrorKind`],
+/// `detail` the family `ErrorKind` and `inner` the inner `Error`.
+/// Map inner errors that do not implement `Serialize` using [`Error::map()`]
+/// or omit them using [`Error::without_inner()`].
+impl<E: Serialize, K: SerdeKind> Serialize for Error<E, K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Error", 4)?;
+        s.serialize_field("family", K::NAME)?;
+        s.serialize_field("kind", &self.hal_kind())?;
+        s.serialize_field("detail", &K::Shim::from(self.kind))?;
+        s.serialize_field("inner", &self.inner)?;
+        s.end()
+    }
+}
+
+/// Fails if `family` does not match [`Family::NAME`]
+impl<'de, E: Deserialize<'de>, K: SerdeKind> Deserialize<'de> for Error<E, K> {
+    #[track_caller]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = Repr::<K, E>::deserialize(deserializer)?;
+        Ok(Self::new(repr.inner, repr.detail.into()))
+    }
+}
+
+/// [`Error`] without its inner error
+///
+/// Serialized as a struct `{family, kind, detail}` like [`Error`] but without `inner`.
+/// Use it for inner errors that are not serializable or not relevant to the receiver.
+/// Deserializes into an `Error<(), K>` through [`WithoutInner::into_error()`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WithoutInner<K>(K);
+
+impl<E, K: Copy> Error<E, K> {
+    /// Serializable view of the error without its inner error
+    pub fn without_inner(&self) -> WithoutInner<K> {
+        WithoutInner(self.kind)
+    }
+}
+
+impl<K> WithoutInner<K> {
+    /// Convert into an [`Error`] with unit inner error
+    #[track_caller]
+    pub fn into_error(self) -> Error<(), K> {
+        Error::new((), self.0)
+    }
+}
+
+impl<K> From<WithoutInner<K>> for Error<(), K> {
+    #[track_caller]
+    fn from(value: WithoutInner<K>) -> Self {
+        value.into_error()
+    }
+}
+
+impl<K: SerdeKind> Serialize for WithoutInner<K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Error", 3)?;
+        s.serialize_field("family", K::NAME)?;
+        s.serialize_field("kind", &self.0.into())?;
+        s.serialize_field("detail", &K::Shim::from(self.0))?;
+        s.end()
+    }
+}
+
+/// Fails if `family` does not match [`Family::NAME`]
+impl<'de, K: SerdeKind> Deserialize<'de> for WithoutInner<K> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = BareRepr::<K>::deserialize(deserializer)?;
+        Ok(Self(repr.detail.into()))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Error", bound(deserialize = ""))]
+struct BareRepr<K: SerdeKind> {
+    #[serde(rename = "family")]
+    _family: Name<K>,
+    #[serde(rename = "kind")]
+    _kind: HalErrorKind,
+    detail: K::Shim,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Error", bound(deserialize = "E: Deserialize<'de>"))]
+struct Repr<K: SerdeKind, E> {
+    #[serde(rename = "family")]
+    _family: Name<K>,
+    #[serde(rename = "kind")]
+    _kind: HalErrorKind,
+    detail: K::Shim,
+    inner: E,
+}
+
+/// Checks the family name
+struct Name<K>(PhantomData<K>);
+
+impl<'de, K: Family> Deserialize<'de> for Name<K> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<K>(PhantomData<K>);
+
+        impl<K: Family> de::Visitor<'_> for Visitor<K> {
+            type Value = Name<K>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "the family name {:?}", K::NAME)
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                if v == K::NAME {
+                    Ok(Name(PhantomData))
+                } else {
+                    Err(E::invalid_value(de::Unexpected::Str(v), &self))
+                }
+            }
+        }
+
+        deserializer.deserialize_str(Visitor(PhantomData))
+    }
+}
+
+/// Deserialize the `detail` of a serialized [`Error`] to its [`Code`]
+///
+/// The `detail` shim type is selected by the family name, i.e. the `family`
+/// field of the serialized `Error`. Fails for families that are not enabled.
+#[derive(Debug, Copy, Clone)]
+pub struct DetailCode<'a>(pub &'a str);
+
+impl<'de> DeserializeSeed<'de> for DetailCode<'_> {
+    type Value = u16;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<u16, D::Error> {
+        fn code<'de, K: SerdeKind + Code, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<u16, D::Error> {
+            let kind: K = K::Shim::deserialize(deserializer)?.into();
+            Ok(kind.code())
+        }
+
+        macro_rules! dispatch {
+            ($(
+                $feature:literal $alias:ident $shim:ident:
+                    $($mod:ident)::+, $error:ident, $kind:ident, $name:literal = $code:literal
+                    $variants:tt
+            )+) => {
+                $(
+                    #[cfg(feature = $feature)]
+                    if self.0 == $name {
+                        return code::<$($mod ::)+ $kind, D>(deserializer);
+                    }
+                )+
+            };
+        }
+        families!(dispatch);
+        if self.0 == Unknown::NAME {
+            return code::<Unknown, D>(deserializer);
+        }
+        Err(de::Error::unknown_variant(self.0, &[]))
+    }
+}
+
+macro_rules! shims {
+    ($(
+        $feature:literal $alias:ident $shim:ident:
+            $($mod:ident)::+, $error:ident, $kind:ident, $family:literal = $code:literal {
+            $(
+                $($name:literal)? $variant:ident
+                $(($arg:ident: $argty:ident {
+                    $($subname:literal $sub:ident = $subcode:literal),+ $(,)?
+                }))?
+                $(= $variant_code:literal)? => $hal:expr
+            ),+ $(,)?
+        }
+    )+) => {$(
+        #[cfg(feature = $feature)]
+        #[doc = concat!("Serializable mirror of `", stringify!($($mod ::)+ $kind), "`")]
+        #[derive(Serialize, Deserialize)]
+        pub enum $shim {
+            $(
+                #[allow(missing_docs)]
+                $variant $((crate::$argty))?,
+            )+
+        }
+
+        #[cfg(feature = $feature)]
+        impl From<$($mod ::)+ $kind> for $shim {
+            fn from(value: $($mod ::)+ $kind) -> Self {
+                use $($mod ::)+ $kind as K;
+                #[allow(unreachable_patterns)]
+                match value {
+                    $(K::$variant $(($arg))? => Self::$variant $(($arg.into()))?,)+
+                    _ => Self::Other,
+                }
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl From<$shim> for $($mod ::)+ $kind {
+            fn from(value: $shim) -> Self {
+                match value {
+                    $($shim::$variant $(($arg))? => Self::$variant $(($arg.into()))?,)+
+                }
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl SerdeKind for $($mod ::)+ $kind {
+            type Shim = $shim;
+        }
+    )+};
+}
+
+families!(shims);
+
+/// Serializable mirror of [`Unknown`]
+#[derive(Serialize, Deserialize)]
+pub enum UnknownErrorKind {
+    #[allow(missing_docs)]
+    Unknown,
+}
+
+impl From<Unknown> for UnknownErrorKind {
+    fn from(_value: Unknown) -> Self {
+        Self::Unknown
+    }
+}
+
+impl From<UnknownErrorKind> for Unknown {
+    fn from(_value: UnknownErrorKind) -> Self {
+        Self
+    }
+}
+
+impl SerdeKind for Unknown {
+    type Shim = UnknownErrorKind;
+}
+
+#[cfg(all(test, feature = "i2c"))]
+mod tests {
+    use embedded_hal::i2c::{self, ErrorKind, NoAcknowledgeSource};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Error(u8);
+    impl i2c::Error for Error {
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+        }
+    }
+
+    #[test]
+    fn postcard() {
+        let err = crate::I2cError::from(Error(0x55));
+        let mut buf = [0; 32];
+        let ser = postcard::to_slice(&err, &mut buf).unwrap();
+        let de: crate::I2cError<Error> = postcard::from_bytes(ser).unwrap();
+        assert_eq!(*de, Error(0x55));
+        assert!(matches!(
+            i2c::Error::kind(&de),
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+        ));
+    }
+
+    #[test]
+    fn shim() {
+        let shim: crate::I2cErrorKind = ErrorKind::Bus.into();
+        assert!(matches!(shim, crate::I2cErrorKind::Bus));
+        let _: <ErrorKind as crate::SerdeKind>::Shim = shim;
+    }
+
+    #[test]
+    fn json() {
+        let err = crate::I2cError::from(Error(0x55));
+        let mut buf = [0; 128];
+        let len = serde_json_core::to_slice(&err, &mut buf).unwrap();
+        assert_eq!(
+            core::str::from_utf8(&buf[..len]).unwrap(),
+            r#"{"family":"i2c","kind":{"NoAcknowledge":"Address"},"detail":{"NoAcknowledge":"Address"},"inner":85}"#
+        );
+        let (de, _): (crate::I2cError<Error>, _) =
+            serde_json_core::from_slice(&buf[..len]).unwrap();
+        assert_eq!(*de, Error(0x55));
+        assert!(matches!(
+            i2c::Error::kind(&de),
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+        ));
+    }
+
+    #[test]
+    fn without_inner() {
+        let err = crate::I2cError::from(Error(0x55));
+        let mut buf = [0; 128];
+        let len = serde_json_core::to_slice(&err.without_inner(), &mut buf).unwrap();
+        assert_eq!(
+            core::str::from_utf8(&buf[..len]).unwrap(),
+            r#"{"family":"i2c","kind":{"NoAcknowledge":"Address"},"detail":{"NoAcknowledge":"Address"}}"#
+        );
+        let (de, _): (crate::WithoutInner<ErrorKind>, _) =
+            serde_json_core::from_slice(&buf[..len]).unwrap();
+        assert_eq!(de, err.without_inner());
+
+        let ser = postcard::to_slice(&err.without_inner(), &mut buf).unwrap();
+        let de: crate::I2cError<()> = postcard::from_bytes::<crate::WithoutInner<_>>(ser)
+            .unwrap()
+            .into();
+        assert_eq!(de.hal_kind(), err.hal_kind());
+    }
+
+    #[test]
+    #[cfg(feature = "spi")]
+    fn family_mismatch() {
+        let err = crate::I2cError::from(Error(0x55));
+        let mut buf = [0; 32];
+        let ser = postcard::to_slice(&err, &mut buf).unwrap();
+        assert!(postcard::from_bytes::<crate::SpiError<Error>>(ser).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! `std::io::Error` interoperability
+
+use core::{error, fmt};
+use std::io;
+
+use crate::{Error, HalErrorKind};
+
+impl From<HalErrorKind> for io::ErrorKind {
+    fn from(value: HalErrorKind) -> Self {
+        use HalErrorKind as K;
+        match value {
+            K::NoAcknowledge(_) | K::NotConnected => Self::NotConnected,
+            K::Timeout => Self::TimedOut,
+            K::Interrupted => Self::Interrupted,
+            K::Unsupported => Self::Unsupported,
+            K::InvalidInput | K::NotAligned | K::OutOfBounds => Self::InvalidInput,
+            K::InvalidData | K::Framing | K::Parity | K::Noise | K::Crc => Self::InvalidData,
+            K::NotFound => Self::NotFound,
+            K::PermissionDenied => Self::PermissionDenied,
+            K::ConnectionRefused => Self::ConnectionRefused,
+            K::ConnectionReset => Self::ConnectionReset,
+            K::ConnectionAborted => Self::ConnectionAborted,
+            K::AddrInUse => Self::AddrInUse,
+            K::AddrNotAvailable => Self::AddrNotAvailable,
+            K::BrokenPipe => Self::BrokenPipe,
+            K::AlreadyExists => Self::AlreadyExists,
+            K::OutOfMemory => Self::OutOfMemory,
+            K::WriteZero => Self::WriteZero,
+            K::Bus
+            | K::ArbitrationLoss
+            | K::Overrun
+            | K::ModeFault
+            | K::ChipSelectFault
+            | K::Other => Self::Other,
+        }
+    }
+}
+
+/// The `std::io::ErrorKind` is mapped from the [`HalErrorKind`].
+/// The [`Error`] is the payload and can be recovered using `downcast()`.
+impl<E, K> From<Error<E, K>> for io::Error
+where
+    E: fmt::Debug + Send + Sync + 'static,
+    K: Copy + Into<HalErrorKind> + error::Error + Send + Sync + 'static,
+{
+    fn from(value: Error<E, K>) -> Self {
+        io::Error::new(value.hal_kind().into(), value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::{HalErrorKind as H, NoAcknowledgeSource as S};
+
+    #[test]
+    fn map() {
+        use io::ErrorKind as K;
+        for (hal, io) in [
+            (H::NoAcknowledge(S::Address), K::NotConnected),
+            (H::Timeout, K::TimedOut),
+            (H::Interrupted, K::Interrupted),
+            (H::NotAligned, K::InvalidInput),
+            (H::Crc, K::InvalidData),
+            (H::BrokenPipe, K::BrokenPipe),
+            (H::Bus, K::Other),
+        ] {
+            assert_eq!(K::from(hal), io);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "i2c")]
+    fn downcast() {
+        use embedded_hal::i2c::{self, ErrorKind, NoAcknowledgeSource};
+
+        #[derive(Debug, PartialEq)]
+        struct Error;
+        impl i2c::Error for Error {
+            fn kind(&self) -> ErrorKind {
+                ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+            }
+        }
+
+        let err = io::Error::from(crate::I2cError::from(Error));
+        assert_eq!(err.kind(), io::ErrorKind::NotConnected);
+        let err: std::boxed::Box<crate::I2cError<Error>> =
+            err.into_inner().unwrap().downcast().unwrap();
+        assert_eq!(**err, Error);
+    }
+
+    #[test]
+    #[cfg(feature = "io")]
+    fn io() {
+        let err: crate::IoError<_> = embedded_io::ErrorKind::TimedOut.into();
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::TimedOut);
+    }
+}
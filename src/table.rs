@@ -0,0 +1,95 @@
+//! Table of the supported HAL families
+//!
+//! The single source of the family `ErrorKind` variants, their names,
+//! numeric codes and unified kinds.
+
+/// Invoke `$callback!` with the table of supported families
+///
+/// A family is
+/// `"feature" Alias Shim: path, Error, ErrorKind, "name" = family_code { variants }`
+/// with the cargo feature, the [`Error`](crate::Error) alias, the `serde` shim,
+/// the upstream module and its `Error` trait and `ErrorKind`,
+/// the [`Family::NAME`](crate::Family::NAME) and the family code.
+///
+/// A variant is `"Name" Variant = code => HalErrorKind`,
+/// or for variants with an argument
+/// `Variant(arg: Type { "Name" Value = code, .. }) => HalErrorKind constructor`.
+/// The argument `Type` exists both upstream and in this crate.
+///
+/// Variants are listed in upstream declaration order which is also the
+/// serialized order of the shims. Codes are frozen.
+macro_rules! families {
+    ($callback:ident) => {
+        $callback! {
+            "digital" DigitalError DigitalErrorKind:
+                embedded_hal::digital, Error, ErrorKind, "digital" = 0x01 {
+                "Other" Other = 0x00 => HalErrorKind::Other,
+            }
+            "i2c" I2cError I2cErrorKind: embedded_hal::i2c, Error, ErrorKind, "i2c" = 0x02 {
+                "Bus" Bus = 0x01 => HalErrorKind::Bus,
+                "ArbitrationLoss" ArbitrationLoss = 0x02 => HalErrorKind::ArbitrationLoss,
+                NoAcknowledge(source: NoAcknowledgeSource {
+                    "NoAcknowledge(Address)" Address = 0x03,
+                    "NoAcknowledge(Data)" Data = 0x04,
+                    "NoAcknowledge(Unknown)" Unknown = 0x05,
+                }) => HalErrorKind::NoAcknowledge,
+                "Overrun" Overrun = 0x06 => HalErrorKind::Overrun,
+                "Other" Other = 0x00 => HalErrorKind::Other,
+            }
+            "pwm" PwmError PwmErrorKind: embedded_hal::pwm, Error, ErrorKind, "pwm" = 0x03 {
+                "Other" Other = 0x00 => HalErrorKind::Other,
+            }
+            "spi" SpiError SpiErrorKind: embedded_hal::spi, Error, ErrorKind, "spi" = 0x04 {
+                "Overrun" Overrun = 0x01 => HalErrorKind::Overrun,
+                "ModeFault" ModeFault = 0x02 => HalErrorKind::ModeFault,
+                "FrameFormat" FrameFormat = 0x03 => HalErrorKind::Framing,
+                "ChipSelectFault" ChipSelectFault = 0x04 => HalErrorKind::ChipSelectFault,
+                "Other" Other = 0x00 => HalErrorKind::Other,
+            }
+            "can" CanError CanErrorKind: embedded_can, Error, ErrorKind, "can" = 0x05 {
+                "Overrun" Overrun = 0x01 => HalErrorKind::Overrun,
+                "Bit" Bit = 0x02 => HalErrorKind::Bus,
+                "Stuff" Stuff = 0x03 => HalErrorKind::Framing,
+                "Crc" Crc = 0x04 => HalErrorKind::Crc,
+                "Form" Form = 0x05 => HalErrorKind::Framing,
+                "Acknowledge" Acknowledge = 0x06
+                    => HalErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+                "Other" Other = 0x00 => HalErrorKind::Other,
+            }
+            "serial-nb" SerialError SerialErrorKind:
+                embedded_hal_nb::serial, Error, ErrorKind, "serial" = 0x06 {
+                "Overrun" Overrun = 0x01 => HalErrorKind::Overrun,
+                "FrameFormat" FrameFormat = 0x02 => HalErrorKind::Framing,
+                "Parity" Parity = 0x03 => HalErrorKind::Parity,
+                "Noise" Noise = 0x04 => HalErrorKind::Noise,
+                "Other" Other = 0x00 => HalErrorKind::Other,
+            }
+            "io" IoError IoErrorKind: embedded_io, Error, ErrorKind, "io" = 0x07 {
+                "Other" Other = 0x00 => HalErrorKind::Other,
+                "NotFound" NotFound = 0x01 => HalErrorKind::NotFound,
+                "PermissionDenied" PermissionDenied = 0x02 => HalErrorKind::PermissionDenied,
+                "ConnectionRefused" ConnectionRefused = 0x03 => HalErrorKind::ConnectionRefused,
+                "ConnectionReset" ConnectionReset = 0x04 => HalErrorKind::ConnectionReset,
+                "ConnectionAborted" ConnectionAborted = 0x05 => HalErrorKind::ConnectionAborted,
+                "NotConnected" NotConnected = 0x06 => HalErrorKind::NotConnected,
+                "AddrInUse" AddrInUse = 0x07 => HalErrorKind::AddrInUse,
+                "AddrNotAvailable" AddrNotAvailable = 0x08 => HalErrorKind::AddrNotAvailable,
+                "BrokenPipe" BrokenPipe = 0x09 => HalErrorKind::BrokenPipe,
+                "AlreadyExists" AlreadyExists = 0x0a => HalErrorKind::AlreadyExists,
+                "InvalidInput" InvalidInput = 0x0b => HalErrorKind::InvalidInput,
+                "InvalidData" InvalidData = 0x0c => HalErrorKind::InvalidData,
+                "TimedOut" TimedOut = 0x0d => HalErrorKind::Timeout,
+                "Interrupted" Interrupted = 0x0e => HalErrorKind::Interrupted,
+                "Unsupported" Unsupported = 0x0f => HalErrorKind::Unsupported,
+                "OutOfMemory" OutOfMemory = 0x10 => HalErrorKind::OutOfMemory,
+                "WriteZero" WriteZero = 0x11 => HalErrorKind::WriteZero,
+            }
+            "nor-flash" NorFlashError NorFlashErrorKind:
+                embedded_storage::nor_flash, NorFlashError, NorFlashErrorKind, "nor-flash" = 0x08 {
+                "NotAligned" NotAligned = 0x01 => HalErrorKind::NotAligned,
+                "OutOfBounds" OutOfBounds = 0x02 => HalErrorKind::OutOfBounds,
+                "Other" Other = 0x00 => HalErrorKind::Other,
+            }
+        }
+    };
+}
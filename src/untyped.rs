@@ -0,0 +1,152 @@
+//! Errors without an `ErrorKind`
+
+use core::{error, fmt};
+
+use crate::{Error, Family, HalErrorKind};
+
+/// `ErrorKind` of errors that can not be classified
+///
+/// Converts to [`HalErrorKind::Other`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Unknown;
+
+impl fmt::Display for Unknown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl error::Error for Unknown {}
+
+impl From<Unknown> for HalErrorKind {
+    fn from(_value: Unknown) -> Self {
+        Self::Other
+    }
+}
+
+impl Family for Unknown {
+    const NAME: &'static str = "unknown";
+}
+
+/// [`Error`] for errors without an `ErrorKind`
+pub type UnknownError<E> = Error<E, Unknown>;
+
+impl<E, K> Error<E, K> {
+    /// Wrap an error without an `ErrorKind`, e.g. from `embedded-hal` 0.2
+    ///
+    /// The `ErrorKind` of any family is determined by `classify`.
+    #[track_caller]
+    pub fn untyped(inner: E, classify: impl FnOnce(&E) -> K) -> Self {
+        let kind = classify(&inner);
+        Self::new(inner, kind)
+    }
+}
+
+impl<E> UnknownError<E> {
+    /// Wrap an error without an `ErrorKind` as [`Unknown`]
+    #[track_caller]
+    pub fn unknown(inner: E) -> Self {
+        Self::new(inner, Unknown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::error::Error as _;
+
+    use super::{Unknown, UnknownError};
+    use crate::{Class, Code, HalErrorKind};
+
+    /// Error of an `embedded-hal` 0.2 implementation
+    #[derive(Debug, PartialEq)]
+    enum Error {
+        Timeout,
+        Nack,
+    }
+
+    fn write() -> Result<(), Error> {
+        Err(Error::Nack)
+    }
+
+    #[test]
+    fn unknown() {
+        let err = write().map_err(UnknownError::unknown).unwrap_err();
+        assert_eq!(*err, Error::Nack);
+        assert_eq!(
+            UnknownError::unknown(Error::Timeout).into_inner(),
+            Error::Timeout
+        );
+        assert_eq!(err.hal_kind(), HalErrorKind::Other);
+        assert_eq!(err.class(), Class::Fatal);
+        assert_eq!(err.code(), Unknown.code());
+        let _: &Unknown = err.source().unwrap().downcast_ref().unwrap();
+        assert_eq!(HalErrorKind::find(&err), Some(HalErrorKind::Other));
+    }
+
+    #[test]
+    #[cfg(feature = "i2c")]
+    fn classify() {
+        use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+
+        fn classify(err: &Error) -> ErrorKind {
+            match err {
+                Error::Timeout => ErrorKind::Other,
+                Error::Nack => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+            }
+        }
+
+        let err: crate::I2cError<_> = write()
+            .map_err(|err| crate::Error::untyped(err, classify))
+            .unwrap_err();
+        assert_eq!(
+            err.hal_kind(),
+            HalErrorKind::NoAcknowledge(crate::NoAcknowledgeSource::Unknown)
+        );
+        assert_eq!(err.code(), 0x0205);
+        let kind: &ErrorKind = err.source().unwrap().downcast_ref().unwrap();
+        assert_eq!(*kind, classify(&Error::Nack));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Error(u8);
+
+        let err = UnknownError::unknown(Error(0x55));
+        let mut buf = [0; 32];
+        let ser = postcard::to_slice(&err, &mut buf).unwrap();
+        let de: UnknownError<Error> = postcard::from_bytes(ser).unwrap();
+        assert_eq!(*de, Error(0x55));
+        assert_eq!(de.hal_kind(), HalErrorKind::Other);
+    }
+
+    #[test]
+    #[cfg(feature = "defmt")]
+    fn defmt() {
+        extern crate std;
+        use std::vec::Vec;
+
+        #[derive(Debug, defmt::Format)]
+        struct Error(u8);
+
+        // Interning is mocked by a thread local counter bumped on every use
+        let index = defmt::export::fetch_string_index();
+        let err = UnknownError::unknown(Error(0x55));
+        defmt::export::istr(&<UnknownError<Error> as defmt::Format>::_format_tag());
+        defmt::Format::_format_data(&err);
+
+        let mut expect = Vec::new();
+        expect.extend(index.to_le_bytes()); // format sequence tag
+        expect.extend((index + 1).to_le_bytes()); // "{=str}: {} <- {}"
+        expect.extend(7u32.to_le_bytes()); // "unknown"
+        expect.extend(b"unknown");
+        expect.extend((index + 2).to_le_bytes()); // `HalErrorKind` tag
+        expect.push(29); // `HalErrorKind::Other` discriminant
+        expect.extend((index + 3).to_le_bytes()); // inner `Error` tag
+        expect.push(0x55); // inner `Error` data
+        expect.extend(0u16.to_le_bytes()); // format sequence end
+        assert_eq!(defmt::export::fetch_bytes(), expect);
+    }
+}
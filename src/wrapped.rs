@@ -0,0 +1,613 @@
+//! HAL adapters with [`Error`](crate::Error) as their error type
+
+use crate::Error;
+
+/// Wrap a HAL implementation so that its `Error` is this crate's [`Error`](crate::Error)
+///
+/// Delegates the HAL traits to the inner implementation and wraps its errors.
+/// Since `Error` implements the family `Error` traits, the wrapper is a
+/// valid HAL implementation itself.
+///
+/// With the `location` feature the caller of a method is recorded
+/// as the location of its error.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Wrapped<T>(T);
+
+impl<T> Wrapped<T> {
+    /// Wrap a HAL implementation
+    pub fn new(inner: T) -> Self {
+        Self(inner)
+    }
+
+    /// Reference the inner implementation
+    pub fn inner(&self) -> &T {
+        &self.0
+    }
+
+    /// Mutably reference the inner implementation
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    /// Extract the inner implementation
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// Wrap the error of `res`, recording the caller as its location
+#[track_caller]
+#[allow(dead_code)] // without any of the families
+fn wrap<T, E, K>(res: Result<T, E>) -> Result<T, Error<E, K>>
+where
+    Error<E, K>: From<E>,
+{
+    match res {
+        Ok(value) => Ok(value),
+        Err(err) => Err(Error::from(err)),
+    }
+}
+
+#[cfg(feature = "async")]
+mod asynch;
+
+#[cfg(feature = "digital")]
+mod digital {
+    use embedded_hal::digital::{ErrorType, InputPin, OutputPin, PinState, StatefulOutputPin};
+
+    use super::{wrap, Wrapped};
+    use crate::DigitalError;
+
+    impl<T: ErrorType> ErrorType for Wrapped<T> {
+        type Error = DigitalError<T::Error>;
+    }
+
+    impl<T: OutputPin> OutputPin for Wrapped<T> {
+        #[track_caller]
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            wrap(self.0.set_low())
+        }
+
+        #[track_caller]
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            wrap(self.0.set_high())
+        }
+
+        #[track_caller]
+        fn set_state(&mut self, state: PinState) -> Result<(), Self::Error> {
+            wrap(self.0.set_state(state))
+        }
+    }
+
+    impl<T: StatefulOutputPin> StatefulOutputPin for Wrapped<T> {
+        #[track_caller]
+        fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+            wrap(self.0.is_set_high())
+        }
+
+        #[track_caller]
+        fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+            wrap(self.0.is_set_low())
+        }
+
+        #[track_caller]
+        fn toggle(&mut self) -> Result<(), Self::Error> {
+            wrap(self.0.toggle())
+        }
+    }
+
+    impl<T: InputPin> InputPin for Wrapped<T> {
+        #[track_caller]
+        fn is_high(&mut self) -> Result<bool, Self::Error> {
+            wrap(self.0.is_high())
+        }
+
+        #[track_caller]
+        fn is_low(&mut self) -> Result<bool, Self::Error> {
+            wrap(self.0.is_low())
+        }
+    }
+}
+
+#[cfg(feature = "i2c")]
+mod i2c {
+    use embedded_hal::i2c::{AddressMode, ErrorType, I2c, Operation};
+
+    use super::{wrap, Wrapped};
+    use crate::I2cError;
+
+    impl<T: ErrorType> ErrorType for Wrapped<T> {
+        type Error = I2cError<T::Error>;
+    }
+
+    impl<A: AddressMode, T: I2c<A>> I2c<A> for Wrapped<T> {
+        #[track_caller]
+        fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+            wrap(self.0.read(address, read))
+        }
+
+        #[track_caller]
+        fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+            wrap(self.0.write(address, write))
+        }
+
+        #[track_caller]
+        fn write_read(
+            &mut self,
+            address: A,
+            write: &[u8],
+            read: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            wrap(self.0.write_read(address, write, read))
+        }
+
+        #[track_caller]
+        fn transaction(
+            &mut self,
+            address: A,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            wrap(self.0.transaction(address, operations))
+        }
+    }
+}
+
+#[cfg(feature = "pwm")]
+mod pwm {
+    use embedded_hal::pwm::{ErrorType, SetDutyCycle};
+
+    use super::{wrap, Wrapped};
+    use crate::PwmError;
+
+    impl<T: ErrorType> ErrorType for Wrapped<T> {
+        type Error = PwmError<T::Error>;
+    }
+
+    impl<T: SetDutyCycle> SetDutyCycle for Wrapped<T> {
+        fn max_duty_cycle(&self) -> u16 {
+            self.0.max_duty_cycle()
+        }
+
+        #[track_caller]
+        fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+            wrap(self.0.set_duty_cycle(duty))
+        }
+
+        #[track_caller]
+        fn set_duty_cycle_fully_off(&mut self) -> Result<(), Self::Error> {
+            wrap(self.0.set_duty_cycle_fully_off())
+        }
+
+        #[track_caller]
+        fn set_duty_cycle_fully_on(&mut self) -> Result<(), Self::Error> {
+            wrap(self.0.set_duty_cycle_fully_on())
+        }
+
+        #[track_caller]
+        fn set_duty_cycle_fraction(&mut self, num: u16, denom: u16) -> Result<(), Self::Error> {
+            wrap(self.0.set_duty_cycle_fraction(num, denom))
+        }
+
+        #[track_caller]
+        fn set_duty_cycle_percent(&mut self, percent: u8) -> Result<(), Self::Error> {
+            wrap(self.0.set_duty_cycle_percent(percent))
+        }
+    }
+}
+
+#[cfg(feature = "spi")]
+mod spi {
+    use embedded_hal::spi::{ErrorType, Operation, SpiBus, SpiDevice};
+
+    use super::{wrap, Wrapped};
+    use crate::SpiError;
+
+    impl<T: ErrorType> ErrorType for Wrapped<T> {
+        type Error = SpiError<T::Error>;
+    }
+
+    impl<W: Copy + 'static, T: SpiBus<W>> SpiBus<W> for Wrapped<T> {
+        #[track_caller]
+        fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+            wrap(self.0.read(words))
+        }
+
+        #[track_caller]
+        fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+            wrap(self.0.write(words))
+        }
+
+        #[track_caller]
+        fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+            wrap(self.0.transfer(read, write))
+        }
+
+        #[track_caller]
+        fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+            wrap(self.0.transfer_in_place(words))
+        }
+
+        #[track_caller]
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            wrap(self.0.flush())
+        }
+    }
+
+    impl<W: Copy + 'static, T: SpiDevice<W>> SpiDevice<W> for Wrapped<T> {
+        #[track_caller]
+        fn transaction(&mut self, operations: &mut [Operation<'_, W>]) -> Result<(), Self::Error> {
+            wrap(self.0.transaction(operations))
+        }
+
+        #[track_caller]
+        fn read(&mut self, buf: &mut [W]) -> Result<(), Self::Error> {
+            wrap(SpiDevice::read(&mut self.0, buf))
+        }
+
+        #[track_caller]
+        fn write(&mut self, buf: &[W]) -> Result<(), Self::Error> {
+            wrap(SpiDevice::write(&mut self.0, buf))
+        }
+
+        #[track_caller]
+        fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+            wrap(SpiDevice::transfer(&mut self.0, read, write))
+        }
+
+        #[track_caller]
+        fn transfer_in_place(&mut self, buf: &mut [W]) -> Result<(), Self::Error> {
+            wrap(SpiDevice::transfer_in_place(&mut self.0, buf))
+        }
+    }
+}
+
+#[cfg(feature = "can")]
+mod can {
+    use embedded_can::blocking::Can;
+
+    use super::{wrap, Wrapped};
+    use crate::CanError;
+
+    impl<T: Can> Can for Wrapped<T> {
+        type Frame = T::Frame;
+        type Error = CanError<T::Error>;
+
+        #[track_caller]
+        fn transmit(&mut self, frame: &Self::Frame) -> Result<(), Self::Error> {
+            wrap(self.0.transmit(frame))
+        }
+
+        #[track_caller]
+        fn receive(&mut self) -> Result<Self::Frame, Self::Error> {
+            wrap(self.0.receive())
+        }
+    }
+}
+
+#[cfg(feature = "serial-nb")]
+mod serial {
+    use embedded_hal_nb::{
+        nb,
+        serial::{ErrorType, Read, Write},
+    };
+
+    use super::Wrapped;
+    use crate::{NbResultExt, SerialError};
+
+    impl<T: ErrorType> ErrorType for Wrapped<T> {
+        type Error = SerialError<T::Error>;
+    }
+
+    impl<W: Copy, T: Read<W>> Read<W> for Wrapped<T> {
+        #[track_caller]
+        fn read(&mut self) -> nb::Result<W, Self::Error> {
+            self.0.read().wrap()
+        }
+    }
+
+    impl<W: Copy, T: Write<W>> Write<W> for Wrapped<T> {
+        #[track_caller]
+        fn write(&mut self, word: W) -> nb::Result<(), Self::Error> {
+            self.0.write(word).wrap()
+        }
+
+        #[track_caller]
+        fn flush(&mut self) -> nb::Result<(), Self::Error> {
+            self.0.flush().wrap()
+        }
+    }
+}
+
+#[cfg(feature = "io")]
+mod io {
+    use embedded_io::{ErrorType, Read, Write};
+
+    use super::{wrap, Wrapped};
+    use crate::IoError;
+
+    impl<T: ErrorType> ErrorType for Wrapped<T> {
+        type Error = IoError<T::Error>;
+    }
+
+    impl<T: Read> Read for Wrapped<T> {
+        #[track_caller]
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            wrap(self.0.read(buf))
+        }
+    }
+
+    impl<T: Write> Write for Wrapped<T> {
+        #[track_caller]
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            wrap(self.0.write(buf))
+        }
+
+        #[track_caller]
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            wrap(self.0.flush())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "i2c")]
+    fn i2c() {
+        use core::error::Error as _;
+        use embedded_hal::i2c::{self, Error as _, I2c};
+
+        struct Bus;
+        impl i2c::ErrorType for Bus {
+            type Error = i2c::ErrorKind;
+        }
+        impl I2c for Bus {
+            fn transaction(
+                &mut self,
+                _address: u8,
+                _operations: &mut [i2c::Operation<'_>],
+            ) -> Result<(), Self::Error> {
+                Err(i2c::ErrorKind::Overrun)
+            }
+        }
+
+        fn driver<B: I2c>(bus: &mut B) -> Result<(), B::Error> {
+            bus.write(0x10, &[0])
+        }
+
+        let err = driver(&mut super::Wrapped::new(Bus)).unwrap_err();
+        assert!(matches!(err.kind(), i2c::ErrorKind::Overrun));
+
+        #[cfg(feature = "location")]
+        {
+            let err = super::Wrapped::new(Bus).read(0x10, &mut [0]).unwrap_err();
+            assert_eq!(err.location().line(), line!() - 1);
+        }
+        let kind: &i2c::ErrorKind = err.source().unwrap().downcast_ref().unwrap();
+        assert!(matches!(kind, i2c::ErrorKind::Overrun));
+    }
+
+    #[test]
+    #[cfg(feature = "digital")]
+    fn digital() {
+        use embedded_hal::digital::{self, Error as _, InputPin, OutputPin};
+
+        struct Pin;
+        impl digital::ErrorType for Pin {
+            type Error = digital::ErrorKind;
+        }
+        impl OutputPin for Pin {
+            fn set_low(&mut self) -> Result<(), Self::Error> {
+                Err(digital::ErrorKind::Other)
+            }
+            fn set_high(&mut self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+        impl InputPin for Pin {
+            fn is_high(&mut self) -> Result<bool, Self::Error> {
+                Err(digital::ErrorKind::Other)
+            }
+            fn is_low(&mut self) -> Result<bool, Self::Error> {
+                Ok(true)
+            }
+        }
+
+        let mut pin = super::Wrapped::new(Pin);
+        pin.set_high().unwrap();
+        let err = pin.set_low().unwrap_err();
+        assert!(matches!(err.kind(), digital::ErrorKind::Other));
+        assert!(err.is_fatal());
+        assert!(pin.is_low().unwrap());
+        assert_eq!(*pin.is_high().unwrap_err(), digital::ErrorKind::Other);
+    }
+
+    #[test]
+    #[cfg(feature = "pwm")]
+    fn pwm() {
+        use embedded_hal::pwm::{self, Error as _, SetDutyCycle};
+
+        struct Pwm;
+        impl pwm::ErrorType for Pwm {
+            type Error = pwm::ErrorKind;
+        }
+        impl SetDutyCycle for Pwm {
+            fn max_duty_cycle(&self) -> u16 {
+                100
+            }
+            fn set_duty_cycle(&mut self, _duty: u16) -> Result<(), Self::Error> {
+                Err(pwm::ErrorKind::Other)
+            }
+        }
+
+        let mut pwm = super::Wrapped::new(Pwm);
+        assert_eq!(pwm.max_duty_cycle(), 100);
+        let err = pwm.set_duty_cycle_percent(50).unwrap_err();
+        assert!(matches!(err.kind(), pwm::ErrorKind::Other));
+        assert_eq!(err.hal_kind(), crate::HalErrorKind::Other);
+    }
+
+    #[test]
+    #[cfg(feature = "spi")]
+    fn spi() {
+        use embedded_hal::spi::{self, Error as _, SpiBus, SpiDevice};
+
+        struct Bus;
+        impl spi::ErrorType for Bus {
+            type Error = spi::ErrorKind;
+        }
+        impl SpiBus for Bus {
+            fn read(&mut self, _words: &mut [u8]) -> Result<(), Self::Error> {
+                Err(spi::ErrorKind::Overrun)
+            }
+            fn write(&mut self, _words: &[u8]) -> Result<(), Self::Error> {
+                Ok(())
+            }
+            fn transfer(&mut self, _read: &mut [u8], _write: &[u8]) -> Result<(), Self::Error> {
+                Err(spi::ErrorKind::FrameFormat)
+            }
+            fn transfer_in_place(&mut self, _words: &mut [u8]) -> Result<(), Self::Error> {
+                Err(spi::ErrorKind::FrameFormat)
+            }
+            fn flush(&mut self) -> Result<(), Self::Error> {
+                Err(spi::ErrorKind::ModeFault)
+            }
+        }
+
+        struct Device;
+        impl spi::ErrorType for Device {
+            type Error = spi::ErrorKind;
+        }
+        impl SpiDevice for Device {
+            fn transaction(
+                &mut self,
+                _operations: &mut [spi::Operation<'_, u8>],
+            ) -> Result<(), Self::Error> {
+                Err(spi::ErrorKind::ChipSelectFault)
+            }
+        }
+
+        let mut bus = super::Wrapped::new(Bus);
+        SpiBus::write(&mut bus, &[0]).unwrap();
+        let err = SpiBus::read(&mut bus, &mut [0]).unwrap_err();
+        assert!(matches!(err.kind(), spi::ErrorKind::Overrun));
+        assert!(err.is_transient());
+        let err = bus.flush().unwrap_err();
+        assert!(matches!(err.kind(), spi::ErrorKind::ModeFault));
+
+        let mut dev = super::Wrapped::new(Device);
+        let err = SpiDevice::write(&mut dev, &[0]).unwrap_err();
+        assert!(matches!(err.kind(), spi::ErrorKind::ChipSelectFault));
+        assert!(err.is_fatal());
+    }
+
+    #[test]
+    #[cfg(feature = "can")]
+    fn can() {
+        use embedded_can::{self as can, blocking::Can, Error as _};
+
+        struct Frame;
+        impl can::Frame for Frame {
+            fn new(_id: impl Into<can::Id>, _data: &[u8]) -> Option<Self> {
+                Some(Self)
+            }
+            fn new_remote(_id: impl Into<can::Id>, _dlc: usize) -> Option<Self> {
+                Some(Self)
+            }
+            fn is_extended(&self) -> bool {
+                false
+            }
+            fn is_remote_frame(&self) -> bool {
+                false
+            }
+            fn id(&self) -> can::Id {
+                can::Id::Standard(can::StandardId::ZERO)
+            }
+            fn dlc(&self) -> usize {
+                0
+            }
+            fn data(&self) -> &[u8] {
+                &[]
+            }
+        }
+
+        struct Bus;
+        impl Can for Bus {
+            type Frame = Frame;
+            type Error = can::ErrorKind;
+            fn transmit(&mut self, _frame: &Self::Frame) -> Result<(), Self::Error> {
+                Err(can::ErrorKind::Acknowledge)
+            }
+            fn receive(&mut self) -> Result<Self::Frame, Self::Error> {
+                Err(can::ErrorKind::Crc)
+            }
+        }
+
+        let mut bus = super::Wrapped::new(Bus);
+        let err = bus.transmit(&Frame).unwrap_err();
+        assert!(matches!(err.kind(), can::ErrorKind::Acknowledge));
+        assert!(err.is_transient());
+        let Err(err) = bus.receive() else { panic!() };
+        assert!(matches!(err.kind(), can::ErrorKind::Crc));
+    }
+
+    #[test]
+    #[cfg(feature = "io")]
+    fn io() {
+        use embedded_io::{self as io, Error as _, Read, Write};
+
+        struct Port;
+        impl io::ErrorType for Port {
+            type Error = io::ErrorKind;
+        }
+        impl Read for Port {
+            fn read(&mut self, _buf: &mut [u8]) -> Result<usize, Self::Error> {
+                Err(io::ErrorKind::TimedOut)
+            }
+        }
+        impl Write for Port {
+            fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> Result<(), Self::Error> {
+                Err(io::ErrorKind::BrokenPipe)
+            }
+        }
+
+        let mut port = super::Wrapped::new(Port);
+        assert_eq!(port.write(&[0; 3]).unwrap(), 3);
+        let err = port.read(&mut [0]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(err.is_transient());
+        let err = port.flush().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+        assert!(err.is_fatal());
+    }
+
+    #[test]
+    #[cfg(feature = "serial-nb")]
+    fn serial() {
+        use embedded_hal_nb::{
+            nb,
+            serial::{self, Read},
+        };
+
+        struct Uart(Option<serial::ErrorKind>);
+        impl serial::ErrorType for Uart {
+            type Error = serial::ErrorKind;
+        }
+        impl Read for Uart {
+            fn read(&mut self) -> nb::Result<u8, Self::Error> {
+                Err(self.0.map_or(nb::Error::WouldBlock, nb::Error::Other))
+            }
+        }
+
+        let mut uart = super::Wrapped::new(Uart(None));
+        assert!(matches!(uart.read(), Err(nb::Error::WouldBlock)));
+        uart.inner_mut().0 = Some(serial::ErrorKind::Parity);
+        let Err(nb::Error::Other(err)) = uart.read() else {
+            panic!()
+        };
+        assert!(err.is_transient());
+    }
+}
@@ -0,0 +1,380 @@
+//! Async HAL adapters
+//!
+//! The async traits share the `ErrorType`s of their blocking counterparts.
+//!
+//! `#[track_caller]` has no effect on `async fn`. The methods capture their
+//! caller before returning the future instead.
+
+#[cfg(feature = "location")]
+use core::panic::Location;
+
+use crate::Error;
+
+/// Caller of a method, recorded as the location of its error
+#[derive(Clone, Copy)]
+#[allow(dead_code)] // without any of the families
+struct Caller {
+    #[cfg(feature = "location")]
+    location: &'static Location<'static>,
+}
+
+#[allow(dead_code)]
+impl Caller {
+    #[track_caller]
+    fn new() -> Self {
+        Self {
+            #[cfg(feature = "location")]
+            location: Location::caller(),
+        }
+    }
+
+    fn wrap<T, E, K>(self, res: Result<T, E>) -> Result<T, Error<E, K>>
+    where
+        Error<E, K>: From<E>,
+    {
+        match res {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                #[allow(unused_mut)]
+                let mut err = Error::from(err);
+                #[cfg(feature = "location")]
+                {
+                    err.location = self.location;
+                }
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "digital")]
+mod digital {
+    use core::future::Future;
+    use embedded_hal_async::digital::Wait;
+
+    use super::Caller;
+    use crate::Wrapped;
+
+    impl<T: Wait> Wait for Wrapped<T> {
+        #[track_caller]
+        fn wait_for_high(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.wait_for_high().await) }
+        }
+
+        #[track_caller]
+        fn wait_for_low(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.wait_for_low().await) }
+        }
+
+        #[track_caller]
+        fn wait_for_rising_edge(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.wait_for_rising_edge().await) }
+        }
+
+        #[track_caller]
+        fn wait_for_falling_edge(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.wait_for_falling_edge().await) }
+        }
+
+        #[track_caller]
+        fn wait_for_any_edge(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.wait_for_any_edge().await) }
+        }
+    }
+}
+
+#[cfg(feature = "i2c")]
+mod i2c {
+    use core::future::Future;
+    use embedded_hal_async::i2c::{AddressMode, I2c, Operation};
+
+    use super::Caller;
+    use crate::Wrapped;
+
+    impl<A: AddressMode, T: I2c<A>> I2c<A> for Wrapped<T> {
+        #[track_caller]
+        fn read(
+            &mut self,
+            address: A,
+            read: &mut [u8],
+        ) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.read(address, read).await) }
+        }
+
+        #[track_caller]
+        fn write(
+            &mut self,
+            address: A,
+            write: &[u8],
+        ) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.write(address, write).await) }
+        }
+
+        #[track_caller]
+        fn write_read(
+            &mut self,
+            address: A,
+            write: &[u8],
+            read: &mut [u8],
+        ) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.write_read(address, write, read).await) }
+        }
+
+        #[track_caller]
+        fn transaction(
+            &mut self,
+            address: A,
+            operations: &mut [Operation<'_>],
+        ) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.transaction(address, operations).await) }
+        }
+    }
+}
+
+#[cfg(feature = "spi")]
+mod spi {
+    use core::future::Future;
+    use embedded_hal_async::spi::{Operation, SpiDevice};
+
+    use super::Caller;
+    use crate::Wrapped;
+
+    impl<W: Copy + 'static, T: SpiDevice<W>> SpiDevice<W> for Wrapped<T> {
+        #[track_caller]
+        fn transaction(
+            &mut self,
+            operations: &mut [Operation<'_, W>],
+        ) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.transaction(operations).await) }
+        }
+
+        #[track_caller]
+        fn read(&mut self, buf: &mut [W]) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.read(buf).await) }
+        }
+
+        #[track_caller]
+        fn write(&mut self, buf: &[W]) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.write(buf).await) }
+        }
+
+        #[track_caller]
+        fn transfer(
+            &mut self,
+            read: &mut [W],
+            write: &[W],
+        ) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.transfer(read, write).await) }
+        }
+
+        #[track_caller]
+        fn transfer_in_place(
+            &mut self,
+            buf: &mut [W],
+        ) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.transfer_in_place(buf).await) }
+        }
+    }
+}
+
+#[cfg(feature = "io")]
+mod io {
+    use core::future::Future;
+    use embedded_io_async::{Read, Write};
+
+    use super::Caller;
+    use crate::Wrapped;
+
+    impl<T: Read> Read for Wrapped<T> {
+        #[track_caller]
+        fn read(&mut self, buf: &mut [u8]) -> impl Future<Output = Result<usize, Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.read(buf).await) }
+        }
+    }
+
+    impl<T: Write> Write for Wrapped<T> {
+        #[track_caller]
+        fn write(&mut self, buf: &[u8]) -> impl Future<Output = Result<usize, Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.write(buf).await) }
+        }
+
+        #[track_caller]
+        fn flush(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+            let caller = Caller::new();
+            async move { caller.wrap(self.0.flush().await) }
+        }
+    }
+}
+
+#[cfg(all(
+    test,
+    any(feature = "digital", feature = "i2c", feature = "spi", feature = "io")
+))]
+mod tests {
+    extern crate std;
+
+    use core::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll},
+    };
+    use std::{sync::Arc, task::Wake};
+
+    use crate::Wrapped;
+
+    /// Minimal executor polling a future to completion
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        struct Noop;
+        impl Wake for Noop {
+            fn wake(self: Arc<Self>) {}
+        }
+        let waker = Arc::new(Noop).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = pin!(fut);
+        loop {
+            if let Poll::Ready(ret) = fut.as_mut().poll(&mut cx) {
+                return ret;
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "i2c")]
+    fn i2c() {
+        use core::error::Error as _;
+        use embedded_hal_async::i2c::{self, Error as _, I2c};
+
+        struct Bus;
+        impl i2c::ErrorType for Bus {
+            type Error = i2c::ErrorKind;
+        }
+        impl I2c for Bus {
+            async fn transaction(
+                &mut self,
+                _address: u8,
+                _operations: &mut [i2c::Operation<'_>],
+            ) -> Result<(), Self::Error> {
+                Err(i2c::ErrorKind::ArbitrationLoss)
+            }
+        }
+
+        let err = block_on(Wrapped::new(Bus).write(0x10, &[0])).unwrap_err();
+        assert!(matches!(err.kind(), i2c::ErrorKind::ArbitrationLoss));
+        let kind: &i2c::ErrorKind = err.source().unwrap().downcast_ref().unwrap();
+        assert!(matches!(kind, i2c::ErrorKind::ArbitrationLoss));
+    }
+
+    #[test]
+    #[cfg(feature = "spi")]
+    fn spi() {
+        use embedded_hal_async::spi::{self, Error as _, SpiDevice};
+
+        struct Device;
+        impl spi::ErrorType for Device {
+            type Error = spi::ErrorKind;
+        }
+        impl SpiDevice for Device {
+            async fn transaction(
+                &mut self,
+                _operations: &mut [spi::Operation<'_, u8>],
+            ) -> Result<(), Self::Error> {
+                Err(spi::ErrorKind::ChipSelectFault)
+            }
+        }
+
+        let mut dev = Wrapped::new(Device);
+        let err = block_on(dev.write(&[0])).unwrap_err();
+        assert!(matches!(err.kind(), spi::ErrorKind::ChipSelectFault));
+        assert!(err.is_fatal());
+        let err = block_on(dev.transfer(&mut [0], &[0])).unwrap_err();
+        assert_eq!(err.hal_kind(), crate::HalErrorKind::ChipSelectFault);
+    }
+
+    #[test]
+    #[cfg(feature = "digital")]
+    fn wait() {
+        use embedded_hal::digital;
+        use embedded_hal_async::digital::Wait;
+
+        struct Pin;
+        impl digital::ErrorType for Pin {
+            type Error = digital::ErrorKind;
+        }
+        impl Wait for Pin {
+            async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+            async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+                Err(digital::ErrorKind::Other)
+            }
+            async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+                unimplemented!()
+            }
+            async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+                unimplemented!()
+            }
+            async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+                unimplemented!()
+            }
+        }
+
+        let mut pin = Wrapped::new(Pin);
+        block_on(pin.wait_for_high()).unwrap();
+        let err = block_on(pin.wait_for_low()).unwrap_err();
+        #[cfg(feature = "location")]
+        assert_eq!(
+            (err.location().file(), err.location().line()),
+            (file!(), line!() - 4)
+        );
+        assert_eq!(err.hal_kind(), crate::HalErrorKind::Other);
+    }
+
+    #[test]
+    #[cfg(feature = "io")]
+    fn io() {
+        use embedded_io_async::{Error as _, ErrorKind, ErrorType, Read, Write};
+
+        struct Stream;
+        impl ErrorType for Stream {
+            type Error = ErrorKind;
+        }
+        impl Read for Stream {
+            async fn read(&mut self, _buf: &mut [u8]) -> Result<usize, Self::Error> {
+                Err(ErrorKind::TimedOut)
+            }
+        }
+        impl Write for Stream {
+            async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+                Ok(buf.len())
+            }
+            async fn flush(&mut self) -> Result<(), Self::Error> {
+                Err(ErrorKind::BrokenPipe)
+            }
+        }
+
+        let mut stream = Wrapped::new(Stream);
+        let err = block_on(stream.read(&mut [0; 4])).unwrap_err();
+        assert!(err.is_transient());
+        assert_eq!(err.hal_kind(), crate::HalErrorKind::Timeout);
+        assert_eq!(block_on(stream.write(&[0; 3])).unwrap(), 3);
+        let err = block_on(stream.flush()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+        assert!(err.is_fatal());
+    }
+}
@@ -0,0 +1,107 @@
+//! Custom family declared outside the crate
+
+use core::{error::Error as _, fmt};
+
+use embedded_hal_error::{family, Class, Code, HalErrorKind};
+
+mod adc {
+    use core::fmt;
+
+    use embedded_hal_error::HalErrorKind;
+
+    pub trait Error: fmt::Debug {
+        fn kind(&self) -> ErrorKind;
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub enum ErrorKind {
+        Overrange,
+        Timeout,
+        Other,
+    }
+
+    impl fmt::Display for ErrorKind {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Debug::fmt(self, f)
+        }
+    }
+
+    impl core::error::Error for ErrorKind {}
+
+    impl From<ErrorKind> for HalErrorKind {
+        fn from(value: ErrorKind) -> Self {
+            match value {
+                ErrorKind::Overrange => Self::OutOfBounds,
+                ErrorKind::Timeout => Self::Timeout,
+                ErrorKind::Other => Self::Other,
+            }
+        }
+    }
+}
+
+family!(
+    /// ADC errors
+    pub type AdcError = adc::Error => adc::ErrorKind, "adc", code 0x80 {
+        adc::ErrorKind::Overrange => 0x01,
+        adc::ErrorKind::Timeout => 0x02,
+    }
+);
+
+#[derive(Debug)]
+struct Error(adc::ErrorKind);
+impl adc::Error for Error {
+    fn kind(&self) -> adc::ErrorKind {
+        self.0
+    }
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("conversion failed")
+    }
+}
+
+fn convert(kind: adc::ErrorKind) -> Result<u16, Error> {
+    Err(Error(kind))
+}
+
+fn read(kind: adc::ErrorKind) -> Result<u16, AdcError<Error>> {
+    Ok(convert(kind)?)
+}
+
+#[test]
+fn from() {
+    use adc::Error as _;
+
+    let err = read(adc::ErrorKind::Overrange).unwrap_err();
+    assert_eq!(err.kind(), adc::ErrorKind::Overrange);
+    assert_eq!(err.hal_kind(), HalErrorKind::OutOfBounds);
+    let kind: &adc::ErrorKind = err.source().unwrap().downcast_ref().unwrap();
+    assert_eq!(*kind, adc::ErrorKind::Overrange);
+}
+
+#[test]
+fn classify() {
+    let err = read(adc::ErrorKind::Timeout).unwrap_err();
+    assert_eq!(err.class(), Class::Transient);
+    assert!(read(adc::ErrorKind::Overrange)
+        .unwrap_err()
+        .is_configuration());
+}
+
+#[test]
+fn code() {
+    assert_eq!(adc::ErrorKind::Overrange.code(), 0x8001);
+    assert_eq!(adc::ErrorKind::Timeout.code(), 0x8002);
+    assert_eq!(adc::ErrorKind::Other.code(), 0x8000);
+    assert_eq!(read(adc::ErrorKind::Timeout).unwrap_err().code(), 0x8002);
+}
+
+#[test]
+fn format() {
+    let err = read(adc::ErrorKind::Other).unwrap_err();
+    assert_eq!(format!("{err}"), "Error(Other)");
+    assert_eq!(
+        format!("{}", embedded_hal_error::Report::new(&err)),
+        "Error(Other): Other"
+    );
+}